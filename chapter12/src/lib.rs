@@ -1,10 +1,13 @@
 use std::error::Error;
 use std::fs;
 
+use regex::{Regex, RegexBuilder};
+
 pub struct CommandConfig {
     pub query: String,
     pub file_path: String,
     pub ignore_case: bool,
+    pub regex: bool,
 }
 
 impl CommandConfig {
@@ -18,20 +21,22 @@ impl CommandConfig {
 
         let additional_args = &args[2..];
 
-        let ignore_case = Self::parse_ignore_case(additional_args);
+        let ignore_case = Self::parse_flag(additional_args, "IGNORE_CASE");
+        let regex = Self::parse_flag(additional_args, "REGEX");
 
         Ok(CommandConfig {
             query,
             file_path,
             ignore_case,
+            regex,
         })
     }
 
     /**
-     * IGNORE_CASE 포함 여부를 반환하는 메서드
+     * 환경 변수나 인수에 key(IGNORE_CASE, REGEX)가 있는지 반환하는 메서드
      */
-    fn parse_ignore_case(args: &[String]) -> bool {
-      let key = String::from("IGNORE_CASE");
+    fn parse_flag(args: &[String], key: &str) -> bool {
+      let key = String::from(key);
 
       std::env::var(&key).is_ok() || args.contains(&key)
     }
@@ -44,7 +49,13 @@ pub fn run(config: CommandConfig) -> Result<(), Box<dyn Error>> {
     // };
 
     let contents = fs::read_to_string(&config.file_path)?;
-    let results = if config.ignore_case {
+    // 메타 문자가 없는 검색어는 정규식 대신 문자열 비교
+    let results = if config.regex && regex::escape(&config.query) != config.query {
+        let regex = RegexBuilder::new(&config.query)
+            .case_insensitive(config.ignore_case)
+            .build()?;
+        search_regex(&regex, &contents)
+    } else if config.ignore_case {
         search_insensitive(&config.query, &contents)
     } else {
         search(&config.query, &contents)
@@ -62,7 +73,7 @@ fn print_searched_lines(results: Vec<&str>) {
     println!("\n+-->");
 }
 
-fn search<'a>(query: &str, contents: &'a str) -> Vec<&'a str> {
+pub fn search<'a>(query: &str, contents: &'a str) -> Vec<&'a str> {
     let mut result = Vec::new();
 
     for content in contents.lines() {
@@ -74,7 +85,7 @@ fn search<'a>(query: &str, contents: &'a str) -> Vec<&'a str> {
     result
 }
 
-fn search_insensitive<'a>(query: &str, contents: &'a str) -> Vec<&'a str> {
+pub fn search_insensitive<'a>(query: &str, contents: &'a str) -> Vec<&'a str> {
     let query = query.to_lowercase();
     let mut result = Vec::new();
 
@@ -87,6 +98,19 @@ fn search_insensitive<'a>(query: &str, contents: &'a str) -> Vec<&'a str> {
     result
 }
 
+/// regex 크레이트는 선형 시간 매칭을 보장
+pub fn search_regex<'a>(regex: &Regex, contents: &'a str) -> Vec<&'a str> {
+    let mut result = Vec::new();
+
+    for content in contents.lines() {
+        if regex.is_match(content) {
+            result.push(content);
+        }
+    }
+
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -114,4 +138,15 @@ Trust me.";
         let result = search_insensitive(query, contents);
         assert_eq!(vec!["Rust:", "Trust me."], result);
     }
+
+    #[test]
+    fn it_work_with_regex() {
+        let regex = Regex::new(r"^[RT]\w+:?$|three").unwrap();
+        let contents = "\
+Rust:
+safe, fast, productive.
+Pick three.
+Trust me.";
+        assert_eq!(vec!["Rust:", "Pick three."], search_regex(&regex, contents));
+    }
 }
@@ -0,0 +1,412 @@
+//! 12장의 루프 검색과 13장의 반복자 검색, 그리고 검색 모드별 속도 비교
+//! 외부 벤치마크 크레이트 없이 std::time::Instant로 측정하므로 오프라인에서도 실행 가능
+//!
+//! ```text
+//! cargo bench                                             # 전체 측정
+//! cargo bench --bench search -- unicode                   # 이름에 unicode가 들어간 것만
+//! cargo bench --bench search -- --save-baseline base.txt  # 결과 저장
+//! cargo bench --bench search -- --baseline base.txt       # 저장한 결과보다 10% 넘게 느려지면 실패
+//! cargo test --benches                                    # 측정 없이 한 번씩만 실행해 결과 확인
+//! ```
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::hint::black_box;
+use std::process;
+use std::time::{Duration, Instant};
+
+use chapter13::{Boundary, MatchOptions, Matcher, Searcher};
+
+/// 말뭉치 하나의 크기
+const CORPUS_SIZE: usize = 1 << 20;
+/// 벤치마크 하나를 측정하는 최소 시간과 반복 횟수 범위
+const MEASURE_TIME: Duration = Duration::from_millis(300);
+const MIN_ITERATIONS: usize = 3;
+const MAX_ITERATIONS: usize = 100;
+/// 기준 결과보다 이 비율을 넘게 느려지면 회귀로 봄
+const REGRESSION_THRESHOLD: f64 = 0.10;
+
+const ASCII_WORDS: &[&str] = &[
+    "safe", "fast", "productive", "pick", "three", "duct", "tape", "trust", "me", "memory",
+    "thread", "borrow", "checker", "lifetime", "iterator", "closure", "zero", "cost", "the", "a",
+    "of", "and", "to", "in", "is", "abstraction", "compiler", "Rust:", "Safe,", "FAST",
+];
+
+const UNICODE_WORDS: &[&str] = &[
+    "러스트", "안전하고", "빠르고", "생산적인", "반복자", "클로저", "Straße", "STRASSE", "café",
+    "Café", "crème", "naïve", "Ελληνικά", "ΣΊΣΥΦΟΣ", "Добрый", "день", "日本語", "検索", "😀",
+    "🦀", "e\u{301}te\u{301}", "ﬁle", "İstanbul", "the", "and",
+];
+
+/// 검색어를 바꿔도 같은 말뭉치가 나오도록 고정 시드를 쓰는 xorshift
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn pick<'a>(&mut self, words: &[&'a str]) -> &'a str {
+        words[self.below(words.len())]
+    }
+}
+
+/// 단어 목록에서 고른 단어로 줄을 만들어 CORPUS_SIZE 바이트까지 채운 말뭉치
+/// line_words는 줄 하나의 단어 수 범위, always가 있으면 모든 줄 가운데쯤에 넣음
+fn generate(words: &[&str], line_words: (usize, usize), always: Option<&str>, seed: u64) -> String {
+    let mut rng = Rng(seed);
+    let mut text = String::with_capacity(CORPUS_SIZE + 1024);
+    while text.len() < CORPUS_SIZE {
+        let count = line_words.0 + rng.below(line_words.1 - line_words.0 + 1);
+        let mut line: Vec<&str> = (0..count).map(|_| rng.pick(words)).collect();
+        if let Some(word) = always {
+            line.insert(count / 2, word);
+        }
+        text.push_str(&line.join(" "));
+        text.push('\n');
+    }
+    text
+}
+
+struct Corpus {
+    name: &'static str,
+    text: String,
+    /// 이 말뭉치에서 찾을 검색어와, 여러 검색어 모드에서 함께 쓸 검색어
+    query: &'static str,
+    other: &'static str,
+}
+
+fn corpora() -> Vec<Corpus> {
+    vec![
+        Corpus {
+            name: "ascii",
+            text: generate(ASCII_WORDS, (4, 14), None, 1),
+            query: "productive",
+            other: "borrow",
+        },
+        Corpus {
+            name: "unicode",
+            text: generate(UNICODE_WORDS, (4, 14), None, 2),
+            query: "café",
+            other: "러스트",
+        },
+        Corpus {
+            name: "long_lines",
+            text: generate(ASCII_WORDS, (8_000, 12_000), None, 3),
+            query: "productive",
+            other: "borrow",
+        },
+        Corpus {
+            name: "many_matches",
+            text: generate(ASCII_WORDS, (4, 14), Some("rust"), 4),
+            query: "rust",
+            other: "three",
+        },
+        Corpus {
+            // 짧은 줄이 많아 검색어만 있는 줄(-x로 찾는 줄)과 다른 단어가 함께 있는 줄이 섞임
+            name: "short_lines",
+            text: generate(ASCII_WORDS, (1, 3), None, 6),
+            query: "productive",
+            other: "borrow",
+        },
+        Corpus {
+            name: "no_matches",
+            text: generate(ASCII_WORDS, (4, 14), None, 5),
+            // 단어 목록에 없는 검색어
+            query: "zyzzyva",
+            other: "xylophone",
+        },
+    ]
+}
+
+/// 말뭉치 하나를 검색해 찾은 줄 수를 돌려주는 검색 방법
+type SearchFn = Box<dyn Fn(&str) -> usize>;
+
+fn matcher(patterns: &[&str], options: MatchOptions) -> Matcher {
+    let patterns: Vec<String> = patterns.iter().map(|pattern| pattern.to_string()).collect();
+    Matcher::build(&patterns, options).unwrap()
+}
+
+/// Searcher로 reader를 한 줄씩 읽는 경로(minigrep이 파일을 검색하는 방법)
+fn searcher_fn(searcher: Searcher) -> SearchFn {
+    Box::new(move |text: &str| searcher.search(text.as_bytes()).count())
+}
+
+fn matcher_fn(matcher: Matcher) -> SearchFn {
+    searcher_fn(Searcher::builder().matcher(matcher).build().unwrap())
+}
+
+/// 말뭉치의 검색어로 만든 검색 방법 목록. 이름은 `구현/모드`
+fn search_modes(corpus: &Corpus) -> Vec<(&'static str, SearchFn)> {
+    let (query, other) = (corpus.query.to_string(), corpus.other);
+    let owned = |query: &str| query.to_string();
+    let ignore_case = MatchOptions {
+        ignore_case: true,
+        ..MatchOptions::default()
+    };
+    let regex = MatchOptions {
+        regex: true,
+        ..MatchOptions::default()
+    };
+
+    vec![
+        ("chapter12/search", {
+            let query = owned(&query);
+            Box::new(move |text: &str| chapter12::search(&query, text).len()) as SearchFn
+        }),
+        ("chapter12/search_insensitive", {
+            let query = owned(&query);
+            Box::new(move |text: &str| chapter12::search_insensitive(&query, text).len())
+        }),
+        ("chapter13/search", {
+            let query = owned(&query);
+            Box::new(move |text: &str| chapter13::search(&query, text).len())
+        }),
+        ("chapter13/search_insensitive", {
+            let query = owned(&query);
+            Box::new(move |text: &str| chapter13::search_insensitive(&query, text).len())
+        }),
+        ("chapter13/search_with_regex", {
+            let matcher = Matcher::new(&format!(r"{query}\w*"), false, true).unwrap();
+            Box::new(move |text: &str| chapter13::search_with(&matcher, text).len())
+        }),
+        (
+            "searcher/literal",
+            matcher_fn(matcher(&[&query], MatchOptions::default())),
+        ),
+        (
+            "searcher/ignore_case",
+            matcher_fn(matcher(&[&query], ignore_case)),
+        ),
+        (
+            "searcher/ignore_diacritics",
+            matcher_fn(matcher(
+                &[&query],
+                MatchOptions {
+                    ignore_diacritics: true,
+                    ..ignore_case
+                },
+            )),
+        ),
+        (
+            "searcher/regex",
+            matcher_fn(matcher(&[&format!(r"{query}\w*")], regex)),
+        ),
+        (
+            "searcher/word",
+            matcher_fn(matcher(
+                &[&query],
+                MatchOptions {
+                    boundary: Boundary::Word,
+                    ..MatchOptions::default()
+                },
+            )),
+        ),
+        (
+            "searcher/line",
+            matcher_fn(matcher(
+                &[&query],
+                MatchOptions {
+                    boundary: Boundary::Line,
+                    ..MatchOptions::default()
+                },
+            )),
+        ),
+        (
+            "searcher/line_regex",
+            matcher_fn(matcher(&[&format!("^{query}$")], regex)),
+        ),
+        (
+            "searcher/patterns",
+            matcher_fn(matcher(&[&query, other], ignore_case)),
+        ),
+        (
+            "searcher/patterns_regex",
+            matcher_fn(matcher(
+                &[&format!(r"{query}\w*"), &format!("^{other}")],
+                regex,
+            )),
+        ),
+        (
+            "searcher/fuzzy",
+            matcher_fn(matcher(
+                &[&query],
+                MatchOptions {
+                    fuzzy: Some(1),
+                    ..ignore_case
+                },
+            )),
+        ),
+        (
+            "searcher/invert",
+            searcher_fn(
+                Searcher::builder()
+                    .query(&query)
+                    .invert(true)
+                    .build()
+                    .unwrap(),
+            ),
+        ),
+        (
+            "searcher/context",
+            searcher_fn(
+                Searcher::builder()
+                    .query(&query)
+                    .context(2, 2)
+                    .build()
+                    .unwrap(),
+            ),
+        ),
+    ]
+}
+
+struct Measurement {
+    median: Duration,
+    lines: usize,
+}
+
+/// 한 번 실행해 예열한 뒤 MEASURE_TIME 동안 반복 실행한 시간의 중앙값
+fn measure(search: &SearchFn, text: &str, quick: bool) -> Measurement {
+    let lines = search(black_box(text));
+    if quick {
+        return Measurement {
+            median: Duration::ZERO,
+            lines,
+        };
+    }
+
+    let started = Instant::now();
+    let mut samples = Vec::new();
+    while samples.len() < MIN_ITERATIONS
+        || (samples.len() < MAX_ITERATIONS && started.elapsed() < MEASURE_TIME)
+    {
+        let start = Instant::now();
+        black_box(search(black_box(text)));
+        samples.push(start.elapsed());
+    }
+    samples.sort();
+    Measurement {
+        median: samples[samples.len() / 2],
+        lines,
+    }
+}
+
+/// `이름\t나노초` 형식의 기준 결과
+fn read_baseline(path: &str) -> BTreeMap<String, u128> {
+    let contents = fs::read_to_string(path).unwrap_or_else(|err| {
+        eprintln!("{path}: {err}");
+        process::exit(2);
+    });
+    contents
+        .lines()
+        .filter_map(|line| {
+            let (name, nanos) = line.split_once('\t')?;
+            Some((name.to_string(), nanos.parse().ok()?))
+        })
+        .collect()
+}
+
+fn main() {
+    // cargo bench는 --bench를 넘김. 없으면 cargo test --benches로 실행된 것이므로 확인만 함
+    let mut quick = true;
+    let mut filter = None;
+    let mut save = None;
+    let mut baseline = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--bench" => quick = false,
+            "--save-baseline" => save = args.next(),
+            "--baseline" => baseline = args.next().map(|path| read_baseline(&path)),
+            _ if arg.starts_with("--") => {}
+            _ => filter = Some(arg),
+        }
+    }
+
+    let mut results = Vec::new();
+    let mut regressions = Vec::new();
+    for corpus in corpora() {
+        let mib = corpus.text.len() as f64 / (1024.0 * 1024.0);
+        let mut found = BTreeMap::new();
+
+        for (mode, search) in search_modes(&corpus) {
+            let name = format!("{}/{mode}", corpus.name);
+            if filter
+                .as_ref()
+                .is_some_and(|filter| !name.contains(filter.as_str()))
+            {
+                continue;
+            }
+
+            let measurement = measure(&search, &corpus.text, quick);
+            found.insert(mode, measurement.lines);
+            if quick {
+                println!("{name:<45} {:>8} lines", measurement.lines);
+                continue;
+            }
+
+            let secs = measurement.median.as_secs_f64();
+            let mut line = format!(
+                "{name:<45} {:>10.3} ms {:>9.1} MiB/s {:>8} lines",
+                secs * 1000.0,
+                mib / secs,
+                measurement.lines
+            );
+            if let Some(before) = baseline.as_ref().and_then(|baseline| baseline.get(&name)) {
+                let change = measurement.median.as_nanos() as f64 / *before as f64 - 1.0;
+                line.push_str(&format!(" {:>+7.1}%", change * 100.0));
+                if change > REGRESSION_THRESHOLD {
+                    regressions.push(name.clone());
+                }
+            }
+            println!("{line}");
+            results.push((name, measurement.median.as_nanos()));
+        }
+
+        // 같은 일을 하는 구현은 같은 줄을 찾아야 비교할 의미가 있음
+        let same = [
+            ("chapter12/search", "chapter13/search"),
+            ("chapter13/search", "searcher/literal"),
+            ("chapter13/search_with_regex", "searcher/regex"),
+            ("searcher/line", "searcher/line_regex"),
+        ];
+        for (first, second) in same {
+            if let (Some(a), Some(b)) = (found.get(first), found.get(second)) {
+                assert_eq!(
+                    a, b,
+                    "{}: {first} and {second} found different lines",
+                    corpus.name
+                );
+            }
+        }
+    }
+
+    if let Some(path) = save {
+        let contents: String = results
+            .iter()
+            .map(|(name, nanos)| format!("{name}\t{nanos}\n"))
+            .collect();
+        if let Err(err) = fs::write(&path, contents) {
+            eprintln!("{path}: {err}");
+            process::exit(2);
+        }
+    }
+    if !regressions.is_empty() {
+        eprintln!(
+            "{} benchmarks are more than {:.0}% slower than the baseline:",
+            regressions.len(),
+            REGRESSION_THRESHOLD * 100.0
+        );
+        for name in &regressions {
+            eprintln!("  {name}");
+        }
+        process::exit(1);
+    }
+}
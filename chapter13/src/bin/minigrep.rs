@@ -0,0 +1,55 @@
+use std::env;
+use std::process;
+
+use chapter13::{CommandConfig, ConfigError, IndexConfig};
+
+// chapter12의 main과 동일. 인수는 벡터 대신 이터레이터로 넘긴다.
+fn main() {
+    // 첫 인수가 index면 검색 대신 색인 생성
+    if env::args().nth(1).as_deref() == Some("index") {
+        let config = IndexConfig::build(env::args().skip(1)).unwrap_or_else(|err| exit_with(err));
+        if let Err(err) = chapter13::run_index(config) {
+            eprintln!("application error: {err}");
+            process::exit(1);
+        }
+        return;
+    }
+
+    let config = CommandConfig::build(env::args()).unwrap_or_else(|err| exit_with(err));
+
+    // JSON 출력은 다른 프로그램이 읽으므로 안내 문구 생략
+    if !config.json {
+        let mut queries = config.regexp.clone();
+        if queries.is_empty() && config.pattern_files.is_empty() {
+            queries.push(config.query.clone());
+        }
+        queries.extend(
+            config
+                .pattern_files
+                .iter()
+                .map(|path| format!("patterns in {}", path.display())),
+        );
+        println!("Searching for {}", queries.join(", "));
+        let paths: Vec<String> = config
+            .file_paths
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+        println!("In {}", paths.join(", "));
+    }
+
+    if let Err(err) = chapter13::run(config) {
+        eprintln!("application error: {err}");
+        process::exit(1);
+    }
+}
+
+/// --help / --version은 표준 출력, 잘못된 인수는 표준 에러로 출력
+fn exit_with(err: ConfigError) -> ! {
+    if err.exit_code() == 0 {
+        println!("{err}");
+    } else {
+        eprintln!("{err}");
+    }
+    process::exit(err.exit_code());
+}
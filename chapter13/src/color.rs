@@ -0,0 +1,127 @@
+use std::env;
+use std::io::{self, IsTerminal};
+use std::ops::Range;
+
+use crate::config::ColorChoice;
+
+/// 색상 설정을 읽어 오는 환경 변수. GREP_COLORS와 같은 `mt=01;31:fn=35` 형식
+pub const COLORS_ENV: &str = "MINIGREP_COLORS";
+
+/// 출력 요소별 ANSI SGR 코드
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    /// 일치한 부분 (mt)
+    pub matched: String,
+    /// 파일 이름 (fn)
+    pub path: String,
+    /// 줄 / 열 번호 (ln)
+    pub line_no: String,
+    /// `:`, `-`, `--` 구분자 (se)
+    pub separator: String,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette {
+            matched: String::from("01;31"),
+            path: String::from("35"),
+            line_no: String::from("32"),
+            separator: String::from("36"),
+        }
+    }
+}
+
+impl Palette {
+    /// --color 값과 출력 대상에 따라 색상을 쓸지 결정. 쓰지 않으면 None
+    /// auto는 표준 출력이 터미널이고 NO_COLOR, TERM=dumb가 아닐 때만 색상 사용
+    pub fn for_choice(choice: ColorChoice) -> Option<Self> {
+        let enabled = match choice {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                io::stdout().is_terminal()
+                    && env::var_os("NO_COLOR").is_none()
+                    && env::var("TERM").map_or(true, |term| term != "dumb")
+            }
+        };
+
+        if enabled {
+            Some(Self::parse(&env::var(COLORS_ENV).unwrap_or_default()))
+        } else {
+            None
+        }
+    }
+
+    /// `mt=01;31:fn=35:ln=32:se=36` 형식. 빠진 항목과 모르는 항목은 기본값 유지
+    pub fn parse(spec: &str) -> Self {
+        let mut palette = Palette::default();
+
+        for entry in spec.split(':') {
+            let Some((key, code)) = entry.split_once('=') else {
+                continue;
+            };
+            // SGR 코드는 숫자와 `;`만 허용해 이상한 제어 문자가 섞이지 않도록 함
+            if !code.chars().all(|ch| ch.is_ascii_digit() || ch == ';') {
+                continue;
+            }
+            let code = code.to_string();
+            match key {
+                "mt" => palette.matched = code,
+                "fn" => palette.path = code,
+                "ln" => palette.line_no = code,
+                "se" => palette.separator = code,
+                _ => {}
+            }
+        }
+
+        palette
+    }
+
+    pub fn paint(code: &str, text: &str) -> String {
+        if code.is_empty() || text.is_empty() {
+            return text.to_string();
+        }
+        format!("\x1b[{code}m{text}\x1b[0m")
+    }
+
+    /// spans 구간만 matched 색으로 칠한 줄
+    pub fn highlight(&self, text: &str, spans: &[Range<usize>]) -> String {
+        let mut highlighted = String::with_capacity(text.len());
+        let mut last = 0;
+        for span in spans {
+            highlighted.push_str(&text[last..span.start]);
+            highlighted.push_str(&Self::paint(&self.matched, &text[span.clone()]));
+            last = span.end;
+        }
+        highlighted.push_str(&text[last..]);
+        highlighted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_palette_spec() {
+        let palette = Palette::parse("mt=04;33:fn=:xx=1:ln=\x1b[31m");
+
+        assert_eq!("04;33", palette.matched);
+        assert_eq!("", palette.path);
+        // 숫자가 아닌 코드는 무시하고 기본값 유지
+        assert_eq!(Palette::default().line_no, palette.line_no);
+        assert_eq!(Palette::default(), Palette::parse(""));
+    }
+
+    #[test]
+    fn it_highlights_spans() {
+        let palette = Palette::default();
+
+        assert_eq!(
+            "a \x1b[01;31mRust\x1b[0m b \x1b[01;31mrust\x1b[0m",
+            palette.highlight("a Rust b rust", &[2..6, 9..13])
+        );
+        assert_eq!("plain", palette.highlight("plain", &[]));
+        assert_eq!(None, Palette::for_choice(ColorChoice::Never));
+    }
+}
@@ -0,0 +1,919 @@
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::decode::Encoding;
+
+/// --color 옵션 값
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorChoice {
+    /// 표준 출력이 터미널일 때만 색상 사용
+    Auto,
+    Always,
+    Never,
+}
+
+pub struct CommandConfig {
+    /// 위치 인수로 받은 검색어. -e나 -f를 쓰면 비어 있음
+    pub query: String,
+    /// -e로 받은 검색어. 여러 번 지정 가능
+    pub regexp: Vec<String>,
+    /// 한 줄에 검색어 하나씩 적힌 파일 (-f)
+    pub pattern_files: Vec<PathBuf>,
+    /// 파일 또는 디렉토리. 디렉토리는 재귀적으로 탐색, `-`는 표준 입력
+    pub file_paths: Vec<PathBuf>,
+    pub ignore_case: bool,
+    /// é와 e처럼 발음 구별 기호만 다른 글자를 같게 취급 (정규식과 함께 쓸 수 없음)
+    pub ignore_diacritics: bool,
+    pub regex: bool,
+    /// 검색어와 편집 거리가 이 값 이하인 부분이 있는 줄을 거리 순으로 출력
+    pub fuzzy: Option<usize>,
+    /// 단어 전체와 일치하는 경우만 (-w)
+    pub word_regexp: bool,
+    /// 줄 전체와 일치하는 경우만 (-x). -w보다 우선
+    pub line_regexp: bool,
+    /// 결과 앞에 줄 번호 출력
+    pub line_number: bool,
+    /// 일치한 부분을 이 문자열로 바꿔 출력. 정규식이면 `$1`, `${name}` 사용 가능
+    pub replace: Option<String>,
+    /// 출력하는 대신 파일 자체를 바꿈
+    pub in_place: bool,
+    /// --in-place로 바꾸기 전 원래 파일을 `파일+접미사`로 남김
+    pub backup_suffix: Option<String>,
+    /// --in-place로 바꿀 내용을 diff로 보여 주기만 하고 파일은 그대로 둠
+    pub dry_run: bool,
+    /// 일치하는 줄 대신 파일별 개수 출력
+    pub count: bool,
+    /// 일치하지 않는 줄을 출력
+    pub invert_match: bool,
+    /// 일치하는 줄이 있는 파일 이름만 출력
+    pub files_with_matches: bool,
+    /// 일치하는 줄이 없는 파일 이름만 출력
+    pub files_without_match: bool,
+    /// 파일마다 이만큼 찾으면 그 파일은 더 읽지 않음
+    pub max_count: Option<usize>,
+    pub color: ColorChoice,
+    /// 입력 파일의 인코딩. 기본값은 BOM과 내용으로 판단
+    pub encoding: Encoding,
+    /// 결과를 JSON Lines 이벤트로 출력
+    pub json: bool,
+    /// 검색에 쓸 스레드 수. None이면 CPU 코어 수
+    pub threads: Option<usize>,
+    /// 병렬 검색에서도 파일 결과를 경로 순서대로 출력
+    pub sort_files: bool,
+    /// 검색을 마친 뒤 통계 출력
+    pub stats: bool,
+    /// 일치하는 줄 앞에 함께 출력할 줄 수
+    pub before_context: usize,
+    /// 일치하는 줄 뒤에 함께 출력할 줄 수
+    pub after_context: usize,
+    /// 파일 끝에 이른 뒤에도 늘어나는 내용을 계속 검색 (`tail -F`처럼 로테이션도 따라감)
+    pub follow: bool,
+    /// 검색을 마친 뒤 파일이 바뀔 때마다 바뀐 파일을 다시 검색
+    pub watch: bool,
+}
+
+/// `minigrep index [DIR...]` 하위 명령 설정
+#[derive(Debug, PartialEq)]
+pub struct IndexConfig {
+    /// 색인을 만들 디렉토리. 없으면 현재 디렉토리
+    pub dirs: Vec<PathBuf>,
+}
+
+impl IndexConfig {
+    /// 첫 인수는 하위 명령 이름(`index`)이므로 무시
+    pub fn build(mut args: impl Iterator<Item = String>) -> Result<Self, ConfigError> {
+        args.next();
+
+        let mut dirs = Vec::new();
+        let mut errors = Vec::new();
+        for arg in args {
+            match arg.as_str() {
+                "-h" | "--help" => return Err(ConfigError::Help),
+                _ if arg.len() > 1 && arg.starts_with('-') => {
+                    errors.push(ArgError::UnknownOption(arg))
+                }
+                _ => dirs.push(PathBuf::from(arg)),
+            }
+        }
+        if dirs.is_empty() {
+            dirs.push(PathBuf::from("."));
+        }
+
+        if errors.is_empty() {
+            Ok(IndexConfig { dirs })
+        } else {
+            Err(ConfigError::Invalid(errors))
+        }
+    }
+}
+
+/// 옵션 정의. 파싱과 --help 출력에 함께 사용
+struct OptionSpec {
+    short: Option<char>,
+    long: &'static str,
+    /// 값을 받는 옵션이면 값 이름
+    value: Option<&'static str>,
+    help: &'static str,
+}
+
+const OPTIONS: &[OptionSpec] = &[
+    OptionSpec {
+        short: Some('e'),
+        long: "regexp",
+        value: Some("PATTERN"),
+        help: "PATTERN을 검색어로 사용. 여러 번 지정하면 하나라도 일치하는 줄 출력",
+    },
+    OptionSpec {
+        short: Some('f'),
+        long: "file",
+        value: Some("FILE"),
+        help: "FILE의 각 줄을 검색어로 사용",
+    },
+    OptionSpec {
+        short: Some('i'),
+        long: "ignore-case",
+        value: None,
+        help: "대소문자 구분 없이 검색 (IGNORE_CASE 환경 변수로도 지정 가능)",
+    },
+    OptionSpec {
+        short: None,
+        long: "ignore-diacritics",
+        value: None,
+        help: "발음 구별 기호 무시 (café => cafe)",
+    },
+    OptionSpec {
+        short: Some('E'),
+        long: "regex",
+        value: None,
+        help: "검색어를 정규식으로 취급",
+    },
+    OptionSpec {
+        short: None,
+        long: "fuzzy",
+        value: Some("K"),
+        help: "편집 거리가 K 이하인 부분이 있는 줄을 가까운 순서로 출력 (오타 찾기)",
+    },
+    OptionSpec {
+        short: Some('w'),
+        long: "word-regexp",
+        value: None,
+        help: "앞뒤가 단어 문자가 아닌, 단어 전체와 일치하는 경우만 검색",
+    },
+    OptionSpec {
+        short: Some('x'),
+        long: "line-regexp",
+        value: None,
+        help: "줄 전체와 일치하는 경우만 검색",
+    },
+    OptionSpec {
+        short: Some('n'),
+        long: "line-number",
+        value: None,
+        help: "줄 번호와 열 번호 출력",
+    },
+    OptionSpec {
+        short: None,
+        long: "replace",
+        value: Some("TEXT"),
+        help: "일치한 부분을 TEXT로 바꿔 출력 (정규식이면 $1, ${name}으로 캡처 그룹 참조)",
+    },
+    OptionSpec {
+        short: None,
+        long: "in-place",
+        value: None,
+        help: "--replace 결과로 파일을 직접 수정 (임시 파일에 쓴 뒤 교체)",
+    },
+    OptionSpec {
+        short: None,
+        long: "backup",
+        value: Some("SUFFIX"),
+        help: "--in-place로 수정하기 전 원래 파일을 이름+SUFFIX로 복사",
+    },
+    OptionSpec {
+        short: None,
+        long: "dry-run",
+        value: None,
+        help: "--in-place로 바뀔 내용을 diff로만 출력",
+    },
+    OptionSpec {
+        short: Some('c'),
+        long: "count",
+        value: None,
+        help: "일치하는 줄 대신 파일별 개수 출력",
+    },
+    OptionSpec {
+        short: Some('v'),
+        long: "invert-match",
+        value: None,
+        help: "일치하지 않는 줄 출력",
+    },
+    OptionSpec {
+        short: Some('l'),
+        long: "files-with-matches",
+        value: None,
+        help: "일치하는 줄이 있는 파일 이름만 출력",
+    },
+    OptionSpec {
+        short: Some('L'),
+        long: "files-without-match",
+        value: None,
+        help: "일치하는 줄이 없는 파일 이름만 출력",
+    },
+    OptionSpec {
+        short: Some('m'),
+        long: "max-count",
+        value: Some("NUM"),
+        help: "파일마다 NUM개의 줄을 찾으면 검색 중단",
+    },
+    OptionSpec {
+        short: Some('A'),
+        long: "after-context",
+        value: Some("NUM"),
+        help: "일치하는 줄 뒤의 NUM줄도 출력",
+    },
+    OptionSpec {
+        short: Some('B'),
+        long: "before-context",
+        value: Some("NUM"),
+        help: "일치하는 줄 앞의 NUM줄도 출력",
+    },
+    OptionSpec {
+        short: Some('C'),
+        long: "context",
+        value: Some("NUM"),
+        help: "일치하는 줄 앞뒤의 NUM줄도 출력",
+    },
+    OptionSpec {
+        short: None,
+        long: "color",
+        value: Some("WHEN"),
+        help: "색상 사용 여부: auto, always, never (기본값 auto). 색은 MINIGREP_COLORS로 지정",
+    },
+    OptionSpec {
+        short: None,
+        long: "encoding",
+        value: Some("ENC"),
+        help: "입력 인코딩: auto, utf-8, utf-16le, utf-16be, latin1 (기본값 auto: BOM이 없으면 0 바이트의 위치로 UTF-16을, 그 밖에는 UTF-8로 읽히는지로 UTF-8 / Latin-1을 판단)",
+    },
+    OptionSpec {
+        short: None,
+        long: "json",
+        value: None,
+        help: "결과를 JSON Lines로 출력 (-c, -l, -L보다 우선)",
+    },
+    OptionSpec {
+        short: Some('j'),
+        long: "threads",
+        value: Some("NUM"),
+        help: "NUM개의 스레드로 여러 파일을 나눠 검색 (기본값 CPU 코어 수)",
+    },
+    OptionSpec {
+        short: None,
+        long: "sort-files",
+        value: None,
+        help: "끝난 순서 대신 경로 순서대로 결과 출력",
+    },
+    OptionSpec {
+        short: None,
+        long: "stats",
+        value: None,
+        help: "검색한 파일 수, 일치한 줄 수, 처리 속도 출력",
+    },
+    OptionSpec {
+        short: None,
+        long: "follow",
+        value: None,
+        help: "tail -F처럼 파일 하나가 늘어나는 대로 계속 검색 (잘리거나 로테이션되어도 따라감)",
+    },
+    OptionSpec {
+        short: None,
+        long: "watch",
+        value: None,
+        help: "검색한 뒤에도 종료하지 않고, 파일이 생기거나 바뀔 때마다 그 파일을 다시 검색",
+    },
+    OptionSpec {
+        short: Some('h'),
+        long: "help",
+        value: None,
+        help: "도움말 출력",
+    },
+    OptionSpec {
+        short: Some('V'),
+        long: "version",
+        value: None,
+        help: "버전 출력",
+    },
+];
+
+/// 잘못된 인수 하나
+#[derive(Debug, PartialEq)]
+pub enum ArgError {
+    UnknownOption(String),
+    MissingValue(String),
+    InvalidValue { option: String, value: String },
+    /// 값을 받지 않는 옵션에 `--opt=value`를 넘김
+    UnexpectedValue(String),
+    /// 함께 쓸 수 없는 두 옵션
+    Conflict(&'static str, &'static str),
+    /// 앞의 옵션은 뒤의 옵션이 있어야 쓸 수 있음
+    Requires(&'static str, &'static str),
+    MissingQuery,
+}
+
+impl fmt::Display for ArgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgError::UnknownOption(option) => write!(f, "unknown option '{option}'"),
+            ArgError::MissingValue(option) => write!(f, "option '{option}' needs a value"),
+            ArgError::InvalidValue { option, value } => {
+                write!(f, "invalid value '{value}' for option '{option}'")
+            }
+            ArgError::UnexpectedValue(option) => {
+                write!(f, "option '{option}' does not take a value")
+            }
+            ArgError::Conflict(first, second) => {
+                write!(f, "'{first}' cannot be used with '{second}'")
+            }
+            ArgError::Requires(option, required) => {
+                write!(f, "'{option}' requires '{required}'")
+            }
+            ArgError::MissingQuery => write!(f, "there is no query"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ConfigError {
+    /// -h / --help. 에러는 아니지만 검색 없이 종료해야 함
+    Help,
+    /// -V / --version
+    Version,
+    /// 잘못된 인수를 모두 모은 목록
+    Invalid(Vec<ArgError>),
+}
+
+impl ConfigError {
+    /// 도움말 / 버전 출력은 정상 종료, 잘못된 인수는 grep과 같이 2
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ConfigError::Help | ConfigError::Version => 0,
+            ConfigError::Invalid(_) => 2,
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Help => write!(f, "{}", help_text()),
+            ConfigError::Version => write!(f, "minigrep {}", env!("CARGO_PKG_VERSION")),
+            ConfigError::Invalid(errors) => {
+                for error in errors {
+                    writeln!(f, "error: {error}")?;
+                }
+                write!(f, "Try 'minigrep --help' for more information.")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// OPTIONS 목록으로 만든 도움말
+pub fn help_text() -> String {
+    let mut text = String::from(
+        "Usage: minigrep [OPTIONS] QUERY [PATH...]\n\
+       minigrep [OPTIONS] -e PATTERN... [PATH...]\n\
+       minigrep [OPTIONS] -f FILE... [PATH...]\n\
+       minigrep index [DIR...]\n\n\
+PATH가 디렉토리면 하위 디렉토리까지 검색합니다. PATH가 없거나 `-`이면 표준 입력을 검색합니다.\n\
+`minigrep index DIR`로 DIR의 트라이그램 색인을 만들어 두면, DIR을 검색할 때 검색어가 없는 파일은 읽지 않습니다.\n\
+다시 실행하면 바뀐 파일만 색인합니다. `index`라는 단어를 찾으려면 `minigrep -- index`를 사용하세요.\n\n\
+Options:\n",
+    );
+
+    for option in OPTIONS {
+        let short = match option.short {
+            Some(short) => format!("-{short}, "),
+            None => String::from("    "),
+        };
+        let long = match option.value {
+            Some(value) => format!("--{}={value}", option.long),
+            None => format!("--{}", option.long),
+        };
+        text.push_str(&format!("  {short}{long:<20} {}\n", option.help));
+    }
+    text.push_str("  --                       이후 인수는 옵션으로 해석하지 않음\n");
+
+    text
+}
+
+impl CommandConfig {
+    pub fn build(
+        // args를 이터레이터 구현체로 변경
+        mut args: impl Iterator<Item = String>,
+    ) -> Result<Self, ConfigError> {
+        args.next(); // 첫번째 인자는무시
+
+        let mut config = CommandConfig {
+            query: String::new(),
+            regexp: Vec::new(),
+            pattern_files: Vec::new(),
+            file_paths: Vec::new(),
+            // 옵션이 없어도 IGNORE_CASE 환경 변수로 켤 수 있음
+            ignore_case: env::var("IGNORE_CASE").is_ok(),
+            ignore_diacritics: false,
+            regex: false,
+            fuzzy: None,
+            word_regexp: false,
+            line_regexp: false,
+            line_number: false,
+            replace: None,
+            in_place: false,
+            backup_suffix: None,
+            dry_run: false,
+            count: false,
+            invert_match: false,
+            files_with_matches: false,
+            files_without_match: false,
+            max_count: None,
+            color: ColorChoice::Auto,
+            encoding: Encoding::Auto,
+            json: false,
+            threads: None,
+            sort_files: false,
+            stats: false,
+            before_context: 0,
+            after_context: 0,
+            follow: false,
+            watch: false,
+        };
+        let mut positional = Vec::new();
+        let mut errors = Vec::new();
+
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                positional.extend(args.by_ref());
+                break;
+            }
+
+            if let Some(long) = arg.strip_prefix("--") {
+                let (name, inline_value) = match long.split_once('=') {
+                    Some((name, value)) => (name, Some(value.to_string())),
+                    None => (long, None),
+                };
+                let Some(spec) = OPTIONS.iter().find(|spec| spec.long == name) else {
+                    errors.push(ArgError::UnknownOption(arg.clone()));
+                    continue;
+                };
+
+                let value = match (spec.value, inline_value) {
+                    (Some(_), Some(value)) => Some(value),
+                    (Some(_), None) => match args.next() {
+                        Some(value) => Some(value),
+                        None => {
+                            errors.push(ArgError::MissingValue(format!("--{name}")));
+                            continue;
+                        }
+                    },
+                    (None, Some(_)) => {
+                        errors.push(ArgError::UnexpectedValue(format!("--{name}")));
+                        continue;
+                    }
+                    (None, None) => None,
+                };
+                config.apply(spec, value, &mut errors)?;
+            } else if arg.len() > 1 && arg.starts_with('-') {
+                // -in 처럼 짧은 옵션을 묶어서 쓸 수 있음
+                // 값을 받는 옵션은 뒤의 나머지 글자(-A2) 또는 다음 인수(-A 2)가 값
+                for (idx, short) in arg.char_indices().skip(1) {
+                    let Some(spec) = OPTIONS.iter().find(|spec| spec.short == Some(short)) else {
+                        errors.push(ArgError::UnknownOption(format!("-{short}")));
+                        continue;
+                    };
+                    if spec.value.is_none() {
+                        config.apply(spec, None, &mut errors)?;
+                        continue;
+                    }
+
+                    let rest = &arg[idx + short.len_utf8()..];
+                    let value = if rest.is_empty() { args.next() } else { Some(rest.to_string()) };
+                    match value {
+                        Some(value) => config.apply(spec, Some(value), &mut errors)?,
+                        None => errors.push(ArgError::MissingValue(format!("-{short}"))),
+                    }
+                    break;
+                }
+            } else {
+                positional.push(arg);
+            }
+        }
+
+        // -e나 -f로 검색어를 받았으면 위치 인수는 모두 경로
+        let mut positional = positional.into_iter();
+        if config.regexp.is_empty() && config.pattern_files.is_empty() {
+            match positional.next() {
+                Some(query) => config.query = query,
+                None => errors.push(ArgError::MissingQuery),
+            }
+        }
+        config.file_paths = positional.map(PathBuf::from).collect();
+        // 경로가 없으면 grep처럼 표준 입력 검색
+        if config.file_paths.is_empty() {
+            config.file_paths.push(PathBuf::from("-"));
+        }
+
+        if config.ignore_diacritics && config.regex {
+            errors.push(ArgError::Conflict("--ignore-diacritics", "--regex"));
+        }
+        if config.fuzzy.is_some() {
+            if config.regex {
+                errors.push(ArgError::Conflict("--fuzzy", "--regex"));
+            }
+            if !config.regexp.is_empty() {
+                errors.push(ArgError::Conflict("--fuzzy", "--regexp"));
+            }
+            if !config.pattern_files.is_empty() {
+                errors.push(ArgError::Conflict("--fuzzy", "--file"));
+            }
+            // 거리 순으로 다시 정렬하므로 앞뒤 문맥이 의미 없음
+            if config.before_context > 0 || config.after_context > 0 {
+                errors.push(ArgError::Conflict("--fuzzy", "--context"));
+            }
+        }
+        if config.replace.is_some() {
+            if config.invert_match {
+                errors.push(ArgError::Conflict("--replace", "--invert-match"));
+            }
+            if config.json {
+                errors.push(ArgError::Conflict("--replace", "--json"));
+            }
+        }
+        if config.in_place {
+            if config.replace.is_none() {
+                errors.push(ArgError::Requires("--in-place", "--replace"));
+            }
+            // 표준 입력은 다시 쓸 파일이 없음
+            if config.file_paths.iter().any(|path| path.as_os_str() == "-") {
+                errors.push(ArgError::Conflict("--in-place", "-"));
+            }
+        }
+        if config.backup_suffix.is_some() && !config.in_place {
+            errors.push(ArgError::Requires("--backup", "--in-place"));
+        }
+        if config.dry_run && !config.in_place {
+            errors.push(ArgError::Requires("--dry-run", "--in-place"));
+        }
+        // 끝나지 않는 입력이므로 파일을 끝까지 읽어야 하는 출력과 함께 쓸 수 없음
+        if config.follow {
+            let conflicts = [
+                (config.watch, "--watch"),
+                (config.count, "--count"),
+                (config.files_with_matches, "--files-with-matches"),
+                (config.files_without_match, "--files-without-match"),
+                (config.fuzzy.is_some(), "--fuzzy"),
+                (config.in_place, "--in-place"),
+            ];
+            for (set, option) in conflicts {
+                if set {
+                    errors.push(ArgError::Conflict("--follow", option));
+                }
+            }
+        }
+        // 표준 입력은 따라가거나 다시 읽을 수 없음
+        let stdin = config.file_paths.iter().any(|path| path.as_os_str() == "-");
+        if config.follow && stdin {
+            errors.push(ArgError::Conflict("--follow", "-"));
+        }
+        if config.watch {
+            // 바꾼 파일이 다시 바뀐 파일로 잡히므로 반복됨
+            if config.in_place {
+                errors.push(ArgError::Conflict("--watch", "--in-place"));
+            }
+            if stdin {
+                errors.push(ArgError::Conflict("--watch", "-"));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(config)
+        } else {
+            Err(ConfigError::Invalid(errors))
+        }
+    }
+
+    /// 검색할 모든 검색어. -e 값 뒤에 -f 파일의 각 줄이 순서대로 옴
+    /// JSON 출력의 검색어 번호는 이 순서를 따름
+    pub fn patterns(&self) -> io::Result<Vec<String>> {
+        if self.regexp.is_empty() && self.pattern_files.is_empty() {
+            return Ok(vec![self.query.clone()]);
+        }
+
+        let mut patterns = self.regexp.clone();
+        for path in &self.pattern_files {
+            let contents = fs::read_to_string(path)
+                .map_err(|err| io::Error::new(err.kind(), format!("{}: {err}", path.display())))?;
+            patterns.extend(
+                contents
+                    .lines()
+                    .map(|line| line.strip_suffix('\r').unwrap_or(line).to_string()),
+            );
+        }
+        Ok(patterns)
+    }
+
+    /// 옵션 하나를 설정에 반영. 도움말 / 버전은 즉시 반환
+    fn apply(
+        &mut self,
+        spec: &OptionSpec,
+        value: Option<String>,
+        errors: &mut Vec<ArgError>,
+    ) -> Result<(), ConfigError> {
+        match spec.long {
+            "ignore-case" => self.ignore_case = true,
+            "ignore-diacritics" => self.ignore_diacritics = true,
+            "regex" => self.regex = true,
+            "word-regexp" => self.word_regexp = true,
+            "line-regexp" => self.line_regexp = true,
+            "line-number" => self.line_number = true,
+            "replace" => self.replace = value,
+            "in-place" => self.in_place = true,
+            "backup" => self.backup_suffix = value,
+            "dry-run" => self.dry_run = true,
+            "count" => self.count = true,
+            "invert-match" => self.invert_match = true,
+            "files-with-matches" => self.files_with_matches = true,
+            "files-without-match" => self.files_without_match = true,
+            "json" => self.json = true,
+            "sort-files" => self.sort_files = true,
+            "stats" => self.stats = true,
+            "follow" => self.follow = true,
+            "watch" => self.watch = true,
+            "regexp" => self.regexp.push(value.unwrap_or_default()),
+            "file" => self.pattern_files.push(PathBuf::from(value.unwrap_or_default())),
+            "encoding" => {
+                let value = value.unwrap_or_default();
+                match Encoding::parse(&value) {
+                    Some(encoding) => self.encoding = encoding,
+                    None => errors.push(ArgError::InvalidValue {
+                        option: String::from("--encoding"),
+                        value,
+                    }),
+                }
+            }
+            "color" => {
+                let value = value.unwrap_or_default();
+                match value.as_str() {
+                    "auto" => self.color = ColorChoice::Auto,
+                    "always" => self.color = ColorChoice::Always,
+                    "never" => self.color = ColorChoice::Never,
+                    _ => errors.push(ArgError::InvalidValue {
+                        option: String::from("--color"),
+                        value,
+                    }),
+                }
+            }
+            "after-context" | "before-context" | "context" | "fuzzy" | "max-count" | "threads" => {
+                let value = value.unwrap_or_default();
+                let Ok(number) = value.parse::<usize>() else {
+                    errors.push(ArgError::InvalidValue {
+                        option: format!("--{}", spec.long),
+                        value,
+                    });
+                    return Ok(());
+                };
+                match spec.long {
+                    "fuzzy" => self.fuzzy = Some(number),
+                    "max-count" => self.max_count = Some(number),
+                    // 스레드가 0개면 아무것도 검색할 수 없음
+                    "threads" if number == 0 => errors.push(ArgError::InvalidValue {
+                        option: String::from("--threads"),
+                        value,
+                    }),
+                    "threads" => self.threads = Some(number),
+                    // 뒤에 오는 옵션이 앞의 값을 덮어씀 (-C 3 -A 1 => 앞 3줄, 뒤 1줄)
+                    "context" => {
+                        self.before_context = number;
+                        self.after_context = number;
+                    }
+                    "before-context" => self.before_context = number,
+                    _ => self.after_context = number,
+                }
+            }
+            "help" => return Err(ConfigError::Help),
+            "version" => return Err(ConfigError::Version),
+            _ => unreachable!("option {} is not handled", spec.long),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(args: &[&str]) -> Result<CommandConfig, ConfigError> {
+        let args = std::iter::once("minigrep")
+            .chain(args.iter().copied())
+            .map(String::from);
+        CommandConfig::build(args)
+    }
+
+    #[test]
+    fn it_parses_short_and_long_flags() {
+        let config = build(&["-in", "--count", "--color=never", "query", "a.txt", "dir"]).unwrap();
+
+        assert!(config.ignore_case);
+        assert!(config.line_number);
+        assert!(config.count);
+        assert!(!config.invert_match);
+        assert_eq!(ColorChoice::Never, config.color);
+        assert_eq!("query", config.query);
+        assert_eq!(
+            vec![PathBuf::from("a.txt"), PathBuf::from("dir")],
+            config.file_paths
+        );
+
+        let config = build(&["--color", "always", "-v", "--encoding=UTF-16LE", "q", "f"]).unwrap();
+        assert_eq!(ColorChoice::Always, config.color);
+        assert_eq!(Encoding::Utf16Le, config.encoding);
+        assert!(config.invert_match);
+
+        let config = build(&["--fuzzy", "2", "q"]).unwrap();
+        assert_eq!(Some(2), config.fuzzy);
+        assert_eq!(
+            Some(ConfigError::Invalid(vec![
+                ArgError::Conflict("--fuzzy", "--regexp"),
+                ArgError::Conflict("--fuzzy", "--context"),
+            ])),
+            build(&["--fuzzy=1", "-e", "q", "-C1"]).err()
+        );
+
+        let config = build(&["-wx", "q"]).unwrap();
+        assert!(config.word_regexp && config.line_regexp);
+
+        let config = build(&["-lm", "3", "-j2", "--stats", "q", "f"]).unwrap();
+        assert_eq!(Some(2), config.threads);
+        assert!(config.stats);
+        assert!(config.files_with_matches);
+        assert!(!config.files_without_match);
+        assert_eq!(Some(3), config.max_count);
+    }
+
+    #[test]
+    fn it_parses_context_options() {
+        let config = build(&["-C", "3", "-A1", "q", "f"]).unwrap();
+        assert_eq!((3, 1), (config.before_context, config.after_context));
+
+        let config = build(&["-nB2", "--after-context=4", "q", "f"]).unwrap();
+        assert!(config.line_number);
+        assert_eq!((2, 4), (config.before_context, config.after_context));
+
+        let Err(ConfigError::Invalid(errors)) = build(&["-C", "many", "q", "f"]) else {
+            panic!("expected invalid context");
+        };
+        assert_eq!(
+            vec![ArgError::InvalidValue {
+                option: String::from("--context"),
+                value: String::from("many"),
+            }],
+            errors
+        );
+    }
+
+    #[test]
+    fn it_collects_patterns_from_e_and_f() {
+        let dir = env::temp_dir().join(format!("minigrep-patterns-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("patterns.txt");
+        fs::write(&file, "three\r\nTrust\n").unwrap();
+
+        let file = file.to_str().unwrap();
+        let config = build(&["-e", "Rust", "--regexp=fast", "-f", file, "a.txt"]).unwrap();
+        // 검색어를 옵션으로 받았으므로 위치 인수는 모두 경로
+        assert_eq!("", config.query);
+        assert_eq!(vec![PathBuf::from("a.txt")], config.file_paths);
+        assert_eq!(
+            vec!["Rust", "fast", "three", "Trust"],
+            config.patterns().unwrap()
+        );
+        assert_eq!(vec!["q"], build(&["q"]).unwrap().patterns().unwrap());
+
+        let config = build(&["-f", dir.join("missing").to_str().unwrap()]).unwrap();
+        assert!(config.patterns().is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn it_reads_stdin_without_paths() {
+        let config = build(&["q"]).unwrap();
+        assert_eq!(vec![PathBuf::from("-")], config.file_paths);
+    }
+
+    #[test]
+    fn it_treats_args_after_double_dash_as_positional() {
+        let config = build(&["--", "-v", "--file"]).unwrap();
+
+        assert!(!config.invert_match);
+        assert_eq!("-v", config.query);
+        assert_eq!(vec![PathBuf::from("--file")], config.file_paths);
+    }
+
+    #[test]
+    fn it_lists_every_invalid_argument() {
+        let Err(ConfigError::Invalid(errors)) = build(&["-k", "--nope", "--color=rainbow", "q"])
+        else {
+            panic!("expected invalid arguments");
+        };
+
+        assert_eq!(
+            vec![
+                ArgError::UnknownOption(String::from("-k")),
+                ArgError::UnknownOption(String::from("--nope")),
+                ArgError::InvalidValue {
+                    option: String::from("--color"),
+                    value: String::from("rainbow"),
+                },
+            ],
+            errors
+        );
+        assert_eq!(Err(ConfigError::Invalid(vec![ArgError::MissingQuery])), build(&[]).map(|_| ()));
+    }
+
+    #[test]
+    fn it_rejects_conflicting_options() {
+        assert_eq!(
+            Some(ConfigError::Invalid(vec![ArgError::Conflict(
+                "--ignore-diacritics",
+                "--regex"
+            )])),
+            build(&["-E", "--ignore-diacritics", "q", "f"]).err()
+        );
+    }
+
+    #[test]
+    fn it_checks_replace_options() {
+        let config = build(&["--replace=$1", "--in-place", "--backup", ".bak", "q", "f"]).unwrap();
+        assert_eq!(Some(String::from("$1")), config.replace);
+        assert!(config.in_place && !config.dry_run);
+        assert_eq!(Some(String::from(".bak")), config.backup_suffix);
+
+        assert_eq!(
+            Some(ConfigError::Invalid(vec![
+                ArgError::Requires("--in-place", "--replace"),
+                ArgError::Conflict("--in-place", "-"),
+            ])),
+            build(&["--in-place", "q"]).err()
+        );
+        assert_eq!(
+            Some(ConfigError::Invalid(vec![
+                ArgError::Requires("--backup", "--in-place"),
+                ArgError::Requires("--dry-run", "--in-place"),
+            ])),
+            build(&["--dry-run", "--backup=~", "q", "f"]).err()
+        );
+    }
+
+    #[test]
+    fn it_checks_follow_and_watch_options() {
+        let config = build(&["--follow", "q", "app.log"]).unwrap();
+        assert!(config.follow && !config.watch);
+        assert!(build(&["--watch", "q", "logs"]).unwrap().watch);
+
+        assert_eq!(
+            Some(ConfigError::Invalid(vec![
+                ArgError::Conflict("--follow", "--watch"),
+                ArgError::Conflict("--follow", "--count"),
+                ArgError::Conflict("--follow", "-"),
+                ArgError::Conflict("--watch", "-"),
+            ])),
+            build(&["--follow", "--watch", "-c", "q"]).err()
+        );
+    }
+
+    #[test]
+    fn it_parses_index_command() {
+        let build = |args: &[&str]| IndexConfig::build(args.iter().map(|arg| arg.to_string()));
+
+        assert_eq!(Ok(IndexConfig { dirs: vec![PathBuf::from(".")] }), build(&["index"]));
+        assert_eq!(
+            Ok(IndexConfig { dirs: vec![PathBuf::from("src"), PathBuf::from("docs")] }),
+            build(&["index", "src", "docs"])
+        );
+        assert_eq!(Err(ConfigError::Help), build(&["index", "-h"]));
+        assert_eq!(
+            Err(ConfigError::Invalid(vec![ArgError::UnknownOption(String::from("-i"))])),
+            build(&["index", "-i"])
+        );
+    }
+
+    #[test]
+    fn it_stops_for_help_and_version() {
+        assert_eq!(Some(ConfigError::Help), build(&["q", "-h"]).err());
+        assert_eq!(Some(ConfigError::Version), build(&["--version"]).err());
+        assert!(help_text().contains("--ignore-case"));
+    }
+}
@@ -0,0 +1,383 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+
+use flate2::bufread::MultiGzDecoder;
+
+/// 바이너리 여부를 판단하기 위해 살펴보는 앞부분 크기 (BufReader 기본 버퍼 크기와 같음)
+const SNIFF_SIZE: usize = 8 * 1024;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const UTF8_BOM: &[u8] = &[0xef, 0xbb, 0xbf];
+const UTF16LE_BOM: &[u8] = &[0xff, 0xfe];
+const UTF16BE_BOM: &[u8] = &[0xfe, 0xff];
+
+/// 입력 파일의 문자 인코딩 (--encoding)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Encoding {
+    /// BOM이 있으면 BOM을 따르고, 없으면 앞부분을 보고 UTF-16(0 바이트의 위치), UTF-8, Latin-1 순으로 선택
+    #[default]
+    Auto,
+    /// UTF-8이 아닌 바이트는 줄마다 대체 문자(U+FFFD)로 바꿈
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    /// ISO-8859-1. 모든 바이트가 같은 번호의 글자
+    Latin1,
+}
+
+impl Encoding {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "auto" => Some(Encoding::Auto),
+            "utf-8" | "utf8" => Some(Encoding::Utf8),
+            "utf-16le" | "utf16le" => Some(Encoding::Utf16Le),
+            "utf-16be" | "utf16be" => Some(Encoding::Utf16Be),
+            "latin1" | "latin-1" | "iso-8859-1" => Some(Encoding::Latin1),
+            _ => None,
+        }
+    }
+}
+
+/// UTF-8 줄로 읽을 수 있게 바꾼 입력
+pub struct Decoded {
+    pub reader: Box<dyn BufRead>,
+    /// gzip을 풀어서 읽는 중인지
+    pub compressed: bool,
+    /// 실제로 사용한 인코딩 (Auto가 아님)
+    pub encoding: Encoding,
+}
+
+impl Decoded {
+    /// 파일이 UTF-8 그대로라서 --in-place로 다시 써도 되는지
+    pub fn is_plain(&self) -> bool {
+        !self.compressed && self.encoding == Encoding::Utf8
+    }
+
+    /// 풀고 바꾼 앞부분에 NUL 바이트가 있으면 바이너리 파일로 취급
+    pub fn is_binary(&mut self) -> io::Result<bool> {
+        Ok(self.reader.fill_buf()?.contains(&0))
+    }
+}
+
+pub fn open(path: &Path, encoding: Encoding) -> io::Result<Decoded> {
+    decode(File::open(path)?, encoding)
+}
+
+/// gzip이면 풀고(첫 두 바이트로 판단), BOM을 건너뛴 뒤 UTF-8이 아니면 UTF-8로 바꾸는 reader를 씌움
+/// 풀 수 없거나 바꿀 수 없는 내용은 읽는 중에 ErrorKind::InvalidData 에러
+pub fn decode(input: impl Read + 'static, encoding: Encoding) -> io::Result<Decoded> {
+    let mut reader: Box<dyn BufRead> = Box::new(BufReader::with_capacity(SNIFF_SIZE, input));
+    let compressed = reader.fill_buf()?.starts_with(GZIP_MAGIC);
+    if compressed {
+        let decoder = Undecodable(MultiGzDecoder::new(reader));
+        reader = Box::new(BufReader::with_capacity(SNIFF_SIZE, decoder));
+    }
+
+    let head = reader.fill_buf()?;
+    let utf16 = looks_like_utf16(head);
+    let utf8 = looks_like_utf8(head);
+    let bom = [
+        (UTF8_BOM, Encoding::Utf8),
+        (UTF16LE_BOM, Encoding::Utf16Le),
+        (UTF16BE_BOM, Encoding::Utf16Be),
+    ]
+    .into_iter()
+    .find(|(bom, bom_encoding)| {
+        head.starts_with(bom) && (encoding == Encoding::Auto || encoding == *bom_encoding)
+    });
+
+    let encoding = match (bom, encoding) {
+        (Some((bom, bom_encoding)), _) => {
+            reader.consume(bom.len());
+            bom_encoding
+        }
+        // ASCII만 있는 UTF-16도 UTF-8로는 읽히므로 먼저 확인
+        (None, Encoding::Auto) => match utf16 {
+            Some(utf16) => utf16,
+            None if utf8 => Encoding::Utf8,
+            None => Encoding::Latin1,
+        },
+        (None, encoding) => encoding,
+    };
+
+    if encoding != Encoding::Utf8 {
+        let transcoder = Transcoder {
+            inner: reader,
+            encoding,
+            out: Vec::new(),
+            pos: 0,
+            odd_byte: None,
+            high_surrogate: None,
+        };
+        reader = Box::new(BufReader::with_capacity(SNIFF_SIZE, transcoder));
+    }
+
+    Ok(Decoded {
+        reader,
+        compressed,
+        encoding,
+    })
+}
+
+/// BOM 없는 UTF-16. 글자 대부분이 U+0100 미만(ASCII 등)이면 2바이트마다 같은 쪽 바이트가 0
+/// 코드 단위의 절반 이상에서 한쪽만 0이고 반대쪽은 거의 0이 아니면 그 바이트 순서로 판단
+fn looks_like_utf16(head: &[u8]) -> Option<Encoding> {
+    let units = head.len() / 2;
+    let (mut first, mut second) = (0, 0);
+    for unit in head.chunks_exact(2) {
+        first += usize::from(unit[0] == 0);
+        second += usize::from(unit[1] == 0);
+    }
+
+    let mostly = |zeros: usize, other: usize| units > 0 && zeros * 2 >= units && other * 8 <= units;
+    if mostly(second, first) {
+        Some(Encoding::Utf16Le)
+    } else if mostly(first, second) {
+        Some(Encoding::Utf16Be)
+    } else {
+        None
+    }
+}
+
+/// 끝이 잘린 글자 외에 잘못된 바이트가 없으면 UTF-8
+fn looks_like_utf8(head: &[u8]) -> bool {
+    match std::str::from_utf8(head) {
+        Ok(_) => true,
+        Err(err) => err.error_len().is_none(),
+    }
+}
+
+/// gzip 해제 중의 에러(손상된 압축 등)는 모두 풀 수 없는 내용으로 취급
+struct Undecodable<R>(R);
+
+impl<R: Read> Read for Undecodable<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf).map_err(|err| match err.kind() {
+            io::ErrorKind::Interrupted | io::ErrorKind::InvalidData => err,
+            _ => io::Error::new(io::ErrorKind::InvalidData, format!("invalid gzip data: {err}")),
+        })
+    }
+}
+
+/// UTF-16 / Latin-1 입력을 UTF-8로 바꿔 읽음
+struct Transcoder<R> {
+    inner: R,
+    encoding: Encoding,
+    /// 바꾼 UTF-8 바이트 중 아직 돌려주지 않은 out[pos..]
+    out: Vec<u8>,
+    pos: usize,
+    /// UTF-16 코드 단위(2바이트)의 첫 바이트만 읽은 경우
+    odd_byte: Option<u8>,
+    /// 서로게이트 쌍의 앞쪽만 읽은 경우
+    high_surrogate: Option<u16>,
+}
+
+impl<R: BufRead> Transcoder<R> {
+    /// 입력 한 덩어리를 바꿔 out에 채움. 입력이 끝났으면 false
+    fn fill(&mut self) -> io::Result<bool> {
+        self.out.clear();
+        self.pos = 0;
+
+        let data = self.inner.fill_buf()?;
+        if data.is_empty() {
+            if self.odd_byte.is_some() || self.high_surrogate.is_some() {
+                return Err(invalid_utf16("input ends in the middle of a character"));
+            }
+            return Ok(false);
+        }
+
+        let len = data.len();
+        match self.encoding {
+            Encoding::Latin1 => {
+                self.out.reserve(len * 2);
+                for &byte in data {
+                    push_char(&mut self.out, char::from(byte));
+                }
+            }
+            _ => {
+                let data = data.to_vec();
+                for byte in data {
+                    let Some(first) = self.odd_byte.take() else {
+                        self.odd_byte = Some(byte);
+                        continue;
+                    };
+                    let unit = if self.encoding == Encoding::Utf16Be {
+                        u16::from_be_bytes([first, byte])
+                    } else {
+                        u16::from_le_bytes([first, byte])
+                    };
+                    self.push_unit(unit)?;
+                }
+            }
+        }
+        self.inner.consume(len);
+        Ok(true)
+    }
+
+    fn push_unit(&mut self, unit: u16) -> io::Result<()> {
+        let ch = match (self.high_surrogate.take(), unit) {
+            (None, 0xd800..=0xdbff) => {
+                self.high_surrogate = Some(unit);
+                return Ok(());
+            }
+            (Some(high), 0xdc00..=0xdfff) => {
+                let code = 0x10000 + ((u32::from(high) - 0xd800) << 10) + (u32::from(unit) - 0xdc00);
+                char::from_u32(code)
+            }
+            (None, 0xdc00..=0xdfff) | (Some(_), _) => None,
+            (None, _) => char::from_u32(u32::from(unit)),
+        };
+
+        match ch {
+            Some(ch) => {
+                push_char(&mut self.out, ch);
+                Ok(())
+            }
+            None => Err(invalid_utf16("unpaired surrogate")),
+        }
+    }
+}
+
+impl<R: BufRead> Read for Transcoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // 서로게이트 쌍의 앞쪽만 있는 덩어리는 바꾼 결과가 없으므로 다음 덩어리까지 읽음
+        while self.pos == self.out.len() {
+            if !self.fill()? {
+                return Ok(0);
+            }
+        }
+
+        let len = buf.len().min(self.out.len() - self.pos);
+        buf[..len].copy_from_slice(&self.out[self.pos..self.pos + len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+fn push_char(out: &mut Vec<u8>, ch: char) {
+    out.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes());
+}
+
+fn invalid_utf16(reason: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("invalid UTF-16: {reason}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    use super::*;
+
+    fn read_all(input: Vec<u8>, encoding: Encoding) -> io::Result<(String, Encoding)> {
+        let mut decoded = decode(io::Cursor::new(input), encoding)?;
+        let mut text = String::new();
+        decoded.reader.read_to_string(&mut text)?;
+        Ok((text, decoded.encoding))
+    }
+
+    fn utf16(text: &str, big_endian: bool) -> Vec<u8> {
+        let mut bytes = if big_endian { UTF16BE_BOM.to_vec() } else { UTF16LE_BOM.to_vec() };
+        for unit in text.encode_utf16() {
+            if big_endian {
+                bytes.extend_from_slice(&unit.to_be_bytes());
+            } else {
+                bytes.extend_from_slice(&unit.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn it_detects_bom() {
+        let text = "Rust:\nPick three. 🦀\n";
+
+        assert_eq!(
+            (text.to_string(), Encoding::Utf16Le),
+            read_all(utf16(text, false), Encoding::Auto).unwrap()
+        );
+        assert_eq!(
+            (text.to_string(), Encoding::Utf16Be),
+            read_all(utf16(text, true), Encoding::Auto).unwrap()
+        );
+        let mut bytes = UTF8_BOM.to_vec();
+        bytes.extend_from_slice(text.as_bytes());
+        assert_eq!(
+            (text.to_string(), Encoding::Utf8),
+            read_all(bytes, Encoding::Auto).unwrap()
+        );
+
+        // BOM 없이 지정한 인코딩
+        let bytes = utf16(text, false)[2..].to_vec();
+        assert_eq!(text, read_all(bytes, Encoding::Utf16Le).unwrap().0);
+    }
+
+    #[test]
+    fn it_detects_utf16_without_bom() {
+        let text = "hello world
+Rust: café 🦀
+";
+        assert_eq!(
+            (text.to_string(), Encoding::Utf16Le),
+            read_all(utf16(text, false)[2..].to_vec(), Encoding::Auto).unwrap()
+        );
+        assert_eq!(
+            (text.to_string(), Encoding::Utf16Be),
+            read_all(utf16(text, true)[2..].to_vec(), Encoding::Auto).unwrap()
+        );
+
+        // 0 바이트가 드문드문 있는 바이너리는 UTF-16이 아님
+        assert_eq!(None, looks_like_utf16(b"abc\0def"));
+        assert_eq!(None, looks_like_utf16(&[0, 0, 0, 0, 7, 1]));
+        assert_eq!(None, looks_like_utf16(b""));
+    }
+
+    #[test]
+    fn it_falls_back_to_latin1() {
+        assert_eq!(
+            (String::from("café crème"), Encoding::Latin1),
+            read_all(b"caf\xe9 cr\xe8me".to_vec(), Encoding::Auto).unwrap()
+        );
+        // UTF-8로 지정하면 바꾸지 않고 그대로 (줄 단위 대체 문자는 검색에서 처리)
+        assert_eq!(
+            Encoding::Utf8,
+            decode(io::Cursor::new(b"caf\xe9".to_vec()), Encoding::Utf8)
+                .unwrap()
+                .encoding
+        );
+    }
+
+    #[test]
+    fn it_detects_binary_after_decoding() {
+        let mut decoded = decode(io::Cursor::new(b"abc\0def".to_vec()), Encoding::Auto).unwrap();
+        assert!(decoded.is_binary().unwrap());
+        // UTF-16의 0 바이트는 바꾼 뒤에는 남지 않음
+        let mut decoded = decode(io::Cursor::new(utf16("abc", false)), Encoding::Auto).unwrap();
+        assert!(!decoded.is_binary().unwrap());
+    }
+
+    #[test]
+    fn it_reads_gzip() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&utf16("safe, fast", false)).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let decoded = decode(io::Cursor::new(compressed.clone()), Encoding::Auto).unwrap();
+        assert!(decoded.compressed && !decoded.is_plain());
+        assert_eq!("safe, fast", read_all(compressed.clone(), Encoding::Auto).unwrap().0);
+
+        let broken = compressed[..compressed.len() / 2].to_vec();
+        let err = read_all(broken, Encoding::Auto).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+
+    #[test]
+    fn it_reports_invalid_utf16() {
+        let err = read_all(vec![0xff, 0xfe, 0x00, 0xd8, b'a', 0], Encoding::Auto).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert!(read_all(vec![0xff, 0xfe, b'a'], Encoding::Auto).is_err());
+    }
+}
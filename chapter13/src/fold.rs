@@ -0,0 +1,279 @@
+use std::ops::Range;
+
+use unicode_normalization::char::{decompose_canonical, is_combining_mark};
+
+mod table;
+
+/// 대소문자(와 선택적으로 발음 구별 기호)를 무시하고 찾는 문자열 검색어
+/// 줄마다 소문자 복사본을 만들지 않고, 글자를 하나씩 접어(fold) 가며 KMP로 비교한다.
+pub struct Folded {
+    /// 접은 검색어
+    query: Vec<char>,
+    /// KMP 실패 함수. failure[i]는 query[..=i]의 접두사이자 접미사인 가장 긴 길이
+    failure: Vec<usize>,
+    /// 접은 검색어가 ASCII면 그 바이트. ASCII 줄은 바이트 단위로 빠르게 비교
+    ascii: Option<Vec<u8>>,
+    fold_case: bool,
+    strip_diacritics: bool,
+}
+
+impl Folded {
+    pub fn new(query: &str, fold_case: bool, strip_diacritics: bool) -> Self {
+        let mut folded = Vec::with_capacity(query.len());
+        for ch in query.chars() {
+            fold_char(ch, fold_case, strip_diacritics, &mut |folded_ch| folded.push(folded_ch));
+        }
+
+        let ascii = folded
+            .iter()
+            .all(char::is_ascii)
+            .then(|| folded.iter().map(|ch| *ch as u8).collect());
+
+        Folded {
+            failure: failure_table(&folded),
+            query: folded,
+            ascii,
+            fold_case,
+            strip_diacritics,
+        }
+    }
+
+    pub fn is_match(&self, line: &str) -> bool {
+        if self.query.is_empty() {
+            return true;
+        }
+
+        // ASCII 줄을 접으면 A-Z만 a-z로 바뀌므로 바이트 비교로 충분
+        if line.is_ascii() {
+            let Some(query) = &self.ascii else {
+                return false;
+            };
+            return line.as_bytes().windows(query.len()).any(|window| {
+                if self.fold_case {
+                    window.eq_ignore_ascii_case(query)
+                } else {
+                    window == query.as_slice()
+                }
+            });
+        }
+
+        let mut state = 0;
+        let mut found = false;
+        self.fold_line(line, |ch, _| {
+            if !found {
+                state = self.step(state, ch);
+                found = state == self.query.len();
+            }
+        });
+        found
+    }
+
+    /// 원래 줄 기준의 일치 구간(바이트 위치). 겹치는 일치는 앞의 것만
+    pub fn find_spans(&self, line: &str) -> Vec<Range<usize>> {
+        if self.query.is_empty() {
+            return Vec::new();
+        }
+
+        // 일치한 줄에서만 호출되므로 접은 글자와 원래 위치를 모아 둬도 부담이 적음
+        let mut folded: Vec<(char, Range<usize>)> = Vec::with_capacity(line.len());
+        self.fold_line(line, |ch, origin| folded.push((ch, origin)));
+
+        let mut spans: Vec<Range<usize>> = Vec::new();
+        let mut state = 0;
+        for (idx, (ch, origin)) in folded.iter().enumerate() {
+            state = self.step(state, *ch);
+            if state == self.query.len() {
+                let start = folded[idx + 1 - state].1.start;
+                // 한 글자가 여러 글자로 접힌 경우(ß => ss) 같은 글자 안에서 다시 일치할 수 있음
+                if spans.last().is_none_or(|last| last.end <= start) {
+                    spans.push(start..origin.end);
+                }
+                state = 0;
+            }
+        }
+        spans
+    }
+
+    /// accept를 만족하는 일치 구간만. 버린 후보와 겹치는 뒤쪽 후보도 확인 (-w)
+    /// KMP는 겹치는 일치를 모두 한 번에 훑으므로 줄 길이에 비례하는 시간
+    pub fn find_accepted(
+        &self,
+        line: &str,
+        accept: &dyn Fn(&Range<usize>) -> bool,
+    ) -> Vec<Range<usize>> {
+        if self.query.is_empty() {
+            return Vec::new();
+        }
+
+        let mut folded: Vec<(char, Range<usize>)> = Vec::with_capacity(line.len());
+        self.fold_line(line, |ch, origin| folded.push((ch, origin)));
+
+        let mut spans: Vec<Range<usize>> = Vec::new();
+        let mut state = 0;
+        for (idx, (ch, origin)) in folded.iter().enumerate() {
+            state = self.step(state, *ch);
+            if state == self.query.len() {
+                let span = folded[idx + 1 - state].1.start..origin.end;
+                if spans.last().is_none_or(|last| last.end <= span.start) && accept(&span) {
+                    spans.push(span);
+                }
+                state = self.failure[state - 1];
+            }
+        }
+        spans
+    }
+
+    fn step(&self, mut state: usize, ch: char) -> usize {
+        while state > 0 && self.query[state] != ch {
+            state = self.failure[state - 1];
+        }
+        if self.query[state] == ch {
+            state + 1
+        } else {
+            0
+        }
+    }
+
+    fn fold_line(&self, line: &str, emit: impl FnMut(char, Range<usize>)) {
+        fold_line(line, self.fold_case, self.strip_diacritics, emit);
+    }
+}
+
+/// 줄의 각 글자를 접어 (접은 글자, 원래 글자의 바이트 구간)으로 전달
+pub fn fold_line(
+    line: &str,
+    fold_case: bool,
+    strip_diacritics: bool,
+    mut emit: impl FnMut(char, Range<usize>),
+) {
+    for (start, ch) in line.char_indices() {
+        let origin = start..start + ch.len_utf8();
+        fold_char(ch, fold_case, strip_diacritics, &mut |folded| {
+            emit(folded, origin.clone())
+        });
+    }
+}
+
+/// 검색어 전체를 접은 문자열
+pub fn fold_str(text: &str, fold_case: bool, strip_diacritics: bool) -> String {
+    let mut folded = String::with_capacity(text.len());
+    fold_line(text, fold_case, strip_diacritics, |ch, _| folded.push(ch));
+    folded
+}
+
+/// 글자 하나를 비교용 글자(들)로 바꿈
+/// - fold_case: Unicode full case folding (ß => ss, ﬁ => fi, ς => σ, ᾳ => αι 등)
+/// - strip_diacritics: 정준 분해 후 결합 문자(é => e, İ => I) 제거, 점 없는 ı도 i로 취급
+fn fold_char(ch: char, fold_case: bool, strip_diacritics: bool, emit: &mut impl FnMut(char)) {
+    if !strip_diacritics {
+        fold_case_char(ch, fold_case, emit);
+        return;
+    }
+    if ch == 'ı' {
+        emit('i');
+        return;
+    }
+
+    decompose_canonical(ch, |part| {
+        if !is_combining_mark(part) {
+            fold_case_char(part, fold_case, emit);
+        }
+    });
+}
+
+fn fold_case_char(ch: char, fold_case: bool, emit: &mut impl FnMut(char)) {
+    if !fold_case {
+        emit(ch);
+        return;
+    }
+
+    // Unicode 기본 접기(i + U+0307) 대신 터키어 접기를 따라 검색어 `i`와 일치하도록
+    if ch == 'İ' {
+        emit('i');
+        return;
+    }
+
+    // 대부분의 글자는 소문자로 바꾸는 것과 같으므로 다른 글자만 표에서 찾음
+    match table::CASE_FOLDING.binary_search_by_key(&ch, |(from, _)| *from) {
+        Ok(idx) => table::CASE_FOLDING[idx].1.chars().for_each(emit),
+        Err(_) => ch.to_lowercase().for_each(emit),
+    }
+}
+
+fn failure_table(query: &[char]) -> Vec<usize> {
+    let mut failure = vec![0; query.len()];
+    let mut len = 0;
+    for idx in 1..query.len() {
+        while len > 0 && query[idx] != query[len] {
+            len = failure[len - 1];
+        }
+        if query[idx] == query[len] {
+            len += 1;
+        }
+        failure[idx] = len;
+    }
+    failure
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_folds_unicode_case() {
+        let folded = Folded::new("STRASSE", true, false);
+        assert!(folded.is_match("Die Straße ist lang"));
+        assert!(folded.is_match("STRASSE"));
+        assert_eq!(vec![4..11], folded.find_spans("Die Straße"));
+
+        let folded = Folded::new("straße", true, false);
+        assert!(folded.is_match("GROSSE STRASSE"));
+
+        // İ는 i로 접히고, 점 없는 ı는 발음 구별 기호를 무시할 때만 i와 같음
+        assert!(Folded::new("istanbul", true, false).is_match("İSTANBUL"));
+        assert!(!Folded::new("ilik", true, false).is_match("ılık"));
+        assert!(Folded::new("ILIK", true, true).is_match("ılık"));
+
+        assert!(Folded::new("ΣΟΦΟΣ", true, false).is_match("σοφος"));
+        assert!(!Folded::new("rust", false, false).is_match("RUST"));
+    }
+
+    #[test]
+    fn it_folds_with_case_folding_table() {
+        // 소문자로 바꾸는 것만으로는 같아지지 않는 글자들
+        assert_eq!("βθ", fold_str("ϐϑ", true, false));
+        assert_eq!("ss", fold_str("ẞ", true, false));
+        assert_eq!("\u{3b9}\u{308}\u{301}", fold_str("ΐ", true, false));
+        // 그리스어 iota subscript, 아르메니아어 합자
+        assert_eq!("αι", fold_str("ᾳ", true, false));
+        assert_eq!("ἀι", fold_str("ᾈ", true, false));
+        assert_eq!("մն", fold_str("ﬓ", true, false));
+        // 체로키 문자는 대문자로 접힘
+        assert_eq!("ᎠᏰ", fold_str("ꭰᏸ", true, false));
+        assert!(Folded::new("ꭰꭱ", true, false).is_match("ᎠᎡ"));
+
+        assert!(Folded::new("ΑΙ", true, false).is_match("ᾼ"));
+        assert!(Folded::new("մնալ", true, false).is_match("ﬓալ"));
+        // binary search를 쓰므로 정렬되어 있어야 함
+        assert!(table::CASE_FOLDING.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn it_ignores_diacritics() {
+        let folded = Folded::new("cafe", true, true);
+        assert!(folded.is_match("Café crème"));
+        // 분해된 형태(e + U+0301)도 같음
+        assert!(folded.is_match("CAFE\u{301}"));
+        assert!(!Folded::new("cafe", true, false).is_match("Café"));
+        assert_eq!(vec![0..5], folded.find_spans("Café"));
+
+        assert!(Folded::new("Ångström", false, true).is_match("Angstrom"));
+    }
+
+    #[test]
+    fn it_uses_kmp_without_missing_overlaps() {
+        let folded = Folded::new("aab", true, false);
+        assert!(folded.is_match("AAAB é"));
+        assert_eq!(vec![1..4, 4..7], folded.find_spans("aAABaab é"));
+    }
+}
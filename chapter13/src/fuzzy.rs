@@ -0,0 +1,163 @@
+use std::ops::Range;
+
+use crate::fold;
+
+/// 편집 거리(Levenshtein)가 max_distance 이하인 부분 문자열을 찾는 검색어
+/// 줄의 어느 위치에서든 시작할 수 있도록 첫 행을 0으로 두는 Sellers 알고리즘.
+/// 한 열씩 계산하며 max_distance를 넘는 아래쪽 행은 계산하지 않음(Ukkonen의 cut-off).
+pub struct Fuzzy {
+    /// 접은 검색어
+    query: Vec<char>,
+    max_distance: usize,
+    fold_case: bool,
+    strip_diacritics: bool,
+}
+
+/// 한 칸의 편집 거리와, 그 거리를 만든 정렬이 시작한 줄의 글자 위치
+#[derive(Clone, Copy)]
+struct Cell {
+    cost: usize,
+    start: usize,
+}
+
+impl Fuzzy {
+    pub fn new(query: &str, max_distance: usize, fold_case: bool, strip_diacritics: bool) -> Self {
+        let mut folded = Vec::with_capacity(query.len());
+        fold::fold_line(query, fold_case, strip_diacritics, |ch, _| folded.push(ch));
+
+        Fuzzy {
+            query: folded,
+            max_distance,
+            fold_case,
+            strip_diacritics,
+        }
+    }
+
+    pub fn is_match(&self, line: &str) -> bool {
+        self.find(line).is_some()
+    }
+
+    /// 가장 가까운 부분의 편집 거리. max_distance보다 멀면 None
+    pub fn distance(&self, line: &str) -> Option<usize> {
+        self.find(line).map(|(_, distance)| distance)
+    }
+
+    /// 편집 거리가 가장 작은 부분(원래 줄 기준 바이트 위치)과 그 거리
+    /// 거리가 같으면 먼저 끝나는 부분
+    pub fn find(&self, line: &str) -> Option<(Range<usize>, usize)> {
+        let mut folded: Vec<(char, Range<usize>)> = Vec::with_capacity(line.len());
+        fold::fold_line(line, self.fold_case, self.strip_diacritics, |ch, origin| {
+            folded.push((ch, origin))
+        });
+
+        let m = self.query.len();
+        // 검색어 글자를 모두 지워도 max_distance 이하면 빈 줄과도 일치
+        let mut best = (m <= self.max_distance).then_some((m, 0..0));
+
+        // column[i]: 검색어 앞 i글자와, 현재 위치에서 끝나는 줄의 부분 문자열 사이의 최소 거리
+        let mut column: Vec<Cell> = (0..=m).map(|i| Cell { cost: i, start: 0 }).collect();
+        // max_distance 이하인 가장 아래 행. 그 아래 행은 다음 열에서도 max_distance를 넘음
+        let mut last_active = self.max_distance.min(m);
+
+        for (j, (ch, origin)) in folded.iter().enumerate() {
+            if best.as_ref().is_some_and(|(cost, _)| *cost == 0) {
+                break;
+            }
+
+            let mut diagonal = column[0];
+            column[0] = Cell {
+                cost: 0,
+                start: j + 1,
+            };
+
+            let rows = (last_active + 1).min(m);
+            for i in 1..=rows {
+                let substitution = Cell {
+                    cost: diagonal.cost + usize::from(self.query[i - 1] != *ch),
+                    start: diagonal.start,
+                };
+                let skip_text = Cell {
+                    cost: column[i].cost + 1,
+                    start: column[i].start,
+                };
+                let skip_query = Cell {
+                    cost: column[i - 1].cost + 1,
+                    start: column[i - 1].start,
+                };
+
+                diagonal = column[i];
+                column[i] = [skip_text, skip_query]
+                    .into_iter()
+                    .fold(
+                        substitution,
+                        |min, cell| if cell.cost < min.cost { cell } else { min },
+                    );
+            }
+            // 계산하지 않은 행은 max_distance보다 크다는 것만 표시
+            if rows < m {
+                column[rows + 1].cost = self.max_distance + 1;
+            }
+
+            last_active = rows;
+            while last_active > 0 && column[last_active].cost > self.max_distance {
+                last_active -= 1;
+            }
+
+            let end = column[m];
+            if rows == m
+                && end.cost <= self.max_distance
+                && best.as_ref().is_none_or(|(cost, _)| end.cost < *cost)
+            {
+                // 검색어를 모두 지운 경우(start > j)는 빈 구간
+                let span = match folded.get(end.start) {
+                    Some((_, first)) if end.start <= j => first.start..origin.end,
+                    _ => origin.end..origin.end,
+                };
+                best = Some((end.cost, span));
+            }
+        }
+
+        best.map(|(cost, span)| (span, cost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_finds_approximate_matches() {
+        let fuzzy = Fuzzy::new("productive", 1, false, false);
+        assert_eq!(Some(0), fuzzy.distance("safe, fast, productive."));
+        assert_eq!(Some(1), fuzzy.distance("safe, fast, productve."));
+        assert_eq!(Some(1), fuzzy.distance("safe, fast, productiv"));
+        assert_eq!(None, fuzzy.distance("safe, fast, prdctive."));
+        assert_eq!(Some((12..21, 1)), fuzzy.find("safe, fast, productve."));
+
+        // 전치(ab => ba)는 편집 두 번
+        assert_eq!(
+            Some(2),
+            Fuzzy::new("productive", 2, false, false).distance("prodcutive")
+        );
+    }
+
+    #[test]
+    fn it_folds_before_measuring() {
+        let fuzzy = Fuzzy::new("strasse", 1, true, false);
+        assert_eq!(Some((4..11, 0)), fuzzy.find("Die STRAßE"));
+        assert_eq!(Some(1), fuzzy.distance("die strase"));
+
+        let fuzzy = Fuzzy::new("cafe", 0, true, true);
+        assert_eq!(Some((2..7, 0)), fuzzy.find("a Café"));
+    }
+
+    #[test]
+    fn it_handles_short_queries() {
+        // 검색어가 거리 이하로 짧으면 모든 줄이 일치
+        let fuzzy = Fuzzy::new("ab", 2, false, false);
+        assert_eq!(Some(2), fuzzy.distance("xyz"));
+        assert_eq!(Some(2), fuzzy.distance(""));
+        assert_eq!(Some((1..2, 1)), fuzzy.find("xaz"));
+        assert_eq!(Some(0), Fuzzy::new("", 0, false, false).distance("Rust"));
+    }
+}
@@ -0,0 +1,235 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 디렉토리마다 읽어 들이는 무시 파일 이름
+pub const IGNORE_FILES: [&str; 2] = [".gitignore", ".ignore"];
+
+/// .gitignore 한 줄
+struct Rule {
+    pattern: String,
+    /// `!`로 시작하면 앞에서 무시한 경로를 다시 포함
+    negate: bool,
+    /// `/`로 끝나면 디렉토리에만 적용
+    dir_only: bool,
+    /// 중간에 `/`가 있으면 무시 파일이 있는 디렉토리 기준 경로와 비교
+    /// 없으면 어느 깊이든 파일 이름만 비교
+    anchored: bool,
+}
+
+/// 한 디렉토리의 무시 파일에서 읽은 규칙 목록
+pub struct IgnoreRules {
+    base: PathBuf,
+    rules: Vec<Rule>,
+}
+
+impl IgnoreRules {
+    /// dir 안의 무시 파일을 읽음. 규칙이 하나도 없으면 None
+    pub fn load(dir: &Path) -> Option<Self> {
+        let contents: Vec<String> = IGNORE_FILES
+            .iter()
+            .filter_map(|name| fs::read_to_string(dir.join(name)).ok())
+            .collect();
+
+        let rules = Self::parse(dir, &contents.join("\n"));
+        if rules.rules.is_empty() {
+            None
+        } else {
+            Some(rules)
+        }
+    }
+
+    pub fn parse(base: &Path, contents: &str) -> Self {
+        IgnoreRules {
+            base: base.to_path_buf(),
+            rules: contents.lines().filter_map(parse_rule).collect(),
+        }
+    }
+
+    /// 일치하는 마지막 규칙의 결과(뒤에서부터 탐색). 일치하는 규칙이 없으면 None
+    fn matched(&self, path: &Path, is_dir: bool) -> Option<bool> {
+        let relative = path.strip_prefix(&self.base).ok()?;
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        let file_name = relative.rsplit('/').next().unwrap_or(&relative);
+
+        self.rules
+            .iter()
+            .rev()
+            .filter(|rule| is_dir || !rule.dir_only)
+            .find(|rule| {
+                if rule.anchored {
+                    glob_match(&rule.pattern, &relative)
+                } else {
+                    glob_match(&rule.pattern, file_name)
+                }
+            })
+            .map(|rule| !rule.negate)
+    }
+}
+
+/// 바깥 디렉토리부터 안쪽 디렉토리 순서로 쌓인 규칙 목록으로 무시 여부 판단
+/// 안쪽 디렉토리의 규칙이 바깥 규칙보다 우선한다.
+pub fn is_ignored(stack: &[IgnoreRules], path: &Path, is_dir: bool) -> bool {
+    stack
+        .iter()
+        .rev()
+        .find_map(|rules| rules.matched(path, is_dir))
+        .unwrap_or(false)
+}
+
+fn parse_rule(line: &str) -> Option<Rule> {
+    let line = line.trim_end();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let (negate, line) = match line.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, line.strip_prefix('\\').unwrap_or(line)),
+    };
+    let (dir_only, line) = match line.strip_suffix('/') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    let anchored = line.contains('/');
+    let pattern = line.trim_start_matches('/').to_string();
+    if pattern.is_empty() {
+        return None;
+    }
+
+    Some(Rule {
+        pattern,
+        negate,
+        dir_only,
+        anchored,
+    })
+}
+
+/// `*`(슬래시 제외 임의 문자열), `?`, `**`(슬래시 포함), `[abc]`, `[a-z]`, `[!a]` 지원
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    glob_match_from(&pattern, &text)
+}
+
+fn glob_match_from(pattern: &[char], text: &[char]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some('*') if pattern.get(1) == Some(&'*') => {
+            // `**/`는 0개 이상의 디렉토리
+            let rest = &pattern[2..];
+            let rest_after_slash = rest.strip_prefix(&['/']).unwrap_or(rest);
+            if glob_match_from(rest_after_slash, text) {
+                return true;
+            }
+            (0..text.len()).any(|idx| glob_match_from(rest, &text[idx + 1..]))
+        }
+        Some('*') => {
+            let rest = &pattern[1..];
+            for idx in 0..=text.len() {
+                if glob_match_from(rest, &text[idx..]) {
+                    return true;
+                }
+                if text.get(idx) == Some(&'/') {
+                    break;
+                }
+            }
+            false
+        }
+        Some('?') => match text.first() {
+            Some(ch) if *ch != '/' => glob_match_from(&pattern[1..], &text[1..]),
+            _ => false,
+        },
+        Some('[') => {
+            let Some(ch) = text.first() else {
+                return false;
+            };
+            match match_class(&pattern[1..], *ch) {
+                Some((true, consumed)) => glob_match_from(&pattern[1 + consumed..], &text[1..]),
+                Some((false, _)) => false,
+                // 닫는 `]`가 없으면 글자 그대로 비교
+                None => *ch == '[' && glob_match_from(&pattern[1..], &text[1..]),
+            }
+        }
+        Some(expected) => match text.first() {
+            Some(ch) if ch == expected => glob_match_from(&pattern[1..], &text[1..]),
+            _ => false,
+        },
+    }
+}
+
+/// `[` 다음부터의 문자 클래스 해석. (일치 여부, `]`까지 소비한 글자 수)
+fn match_class(pattern: &[char], ch: char) -> Option<(bool, usize)> {
+    let (negate, start) = match pattern.first() {
+        Some('!') | Some('^') => (true, 1),
+        _ => (false, 0),
+    };
+
+    let mut idx = start;
+    let mut matched = false;
+    while idx < pattern.len() {
+        let current = pattern[idx];
+        if current == ']' && idx > start {
+            return Some((matched != negate, idx + 1));
+        }
+        if pattern.get(idx + 1) == Some(&'-') && idx + 2 < pattern.len() && pattern[idx + 2] != ']'
+        {
+            if (current..=pattern[idx + 2]).contains(&ch) {
+                matched = true;
+            }
+            idx += 3;
+        } else {
+            if current == ch {
+                matched = true;
+            }
+            idx += 1;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_matches_glob_patterns() {
+        assert!(glob_match("*.log", "debug.log"));
+        assert!(!glob_match("*.log", "logs/debug.log"));
+        assert!(glob_match("**/*.log", "logs/debug.log"));
+        assert!(glob_match("**/*.log", "debug.log"));
+        assert!(glob_match("src/**", "src/a/b.rs"));
+        assert!(glob_match("file?.[ch]", "file1.c"));
+        assert!(!glob_match("file?.[!ch]", "file1.c"));
+        assert!(glob_match("[a-c]at", "bat"));
+    }
+
+    #[test]
+    fn it_applies_gitignore_rules() {
+        let root = Path::new("root");
+        let rules = IgnoreRules::parse(
+            root,
+            "# comment\n*.log\n!keep.log\ntarget/\n/build\ndocs/*.md\n",
+        );
+        let stack = [rules];
+
+        assert!(is_ignored(&stack, Path::new("root/a/debug.log"), false));
+        assert!(!is_ignored(&stack, Path::new("root/a/keep.log"), false));
+        assert!(is_ignored(&stack, Path::new("root/a/target"), true));
+        assert!(!is_ignored(&stack, Path::new("root/a/target"), false));
+        assert!(is_ignored(&stack, Path::new("root/build"), true));
+        assert!(!is_ignored(&stack, Path::new("root/a/build"), true));
+        assert!(is_ignored(&stack, Path::new("root/docs/readme.md"), false));
+        assert!(!is_ignored(&stack, Path::new("root/main.rs"), false));
+    }
+
+    #[test]
+    fn it_lets_inner_rules_override_outer_rules() {
+        let outer = IgnoreRules::parse(Path::new("root"), "*.txt\n");
+        let inner = IgnoreRules::parse(Path::new("root/sub"), "!notes.txt\n");
+        let stack = [outer, inner];
+
+        assert!(is_ignored(&stack, Path::new("root/a.txt"), false));
+        assert!(!is_ignored(&stack, Path::new("root/sub/notes.txt"), false));
+        assert!(is_ignored(&stack, Path::new("root/sub/other.txt"), false));
+    }
+}
@@ -0,0 +1,320 @@
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use crate::decode::{self, Encoding};
+use crate::fold;
+use crate::walk;
+
+/// `minigrep index DIR`가 DIR 안에 만드는 색인 파일. 디렉토리 탐색에서는 제외
+pub const INDEX_FILE: &str = ".minigrep-index";
+
+const MAGIC: &[u8] = b"MINIGREP-INDEX 1\n";
+
+/// 디렉토리 안 파일마다 내용에 들어 있는 트라이그램(연속한 3바이트) 목록
+/// 검색어의 트라이그램이 하나라도 없는 파일은 읽지 않고 건너뛸 수 있다.
+/// 대소문자를 무시한 검색에도 쓸 수 있도록 내용은 Unicode 대소문자 접기를 한 뒤 나눔.
+#[derive(Debug, PartialEq)]
+pub struct Index {
+    root: PathBuf,
+    /// root 기준 상대 경로 => 색인 항목
+    entries: BTreeMap<PathBuf, Entry>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Entry {
+    /// 색인할 때의 수정 시각(UNIX 시간, 나노초)과 크기. 하나라도 다르면 다시 색인
+    modified: u128,
+    len: u64,
+    /// 정렬된 트라이그램
+    trigrams: Vec<u32>,
+}
+
+/// 색인을 갱신한 결과
+#[derive(Debug, Default, PartialEq)]
+pub struct Update {
+    pub files: usize,
+    /// 새로 읽은 파일 수 (새 파일 + 바뀐 파일)
+    pub indexed: usize,
+    pub removed: usize,
+}
+
+impl Index {
+    /// root의 색인 파일을 읽음. 색인이 없으면 None
+    pub fn load(root: &Path) -> io::Result<Option<Self>> {
+        let data = match fs::read(root.join(INDEX_FILE)) {
+            Ok(data) => data,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+
+        let entries = decode(&data).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{}: broken index, rebuild it with 'minigrep index'",
+                    root.join(INDEX_FILE).display()
+                ),
+            )
+        })?;
+        Ok(Some(Index {
+            root: root.to_path_buf(),
+            entries,
+        }))
+    }
+
+    /// 색인을 만들거나, 이미 있으면 수정 시각이 바뀐 파일만 다시 읽어 갱신한 뒤 저장
+    pub fn update(root: &Path) -> io::Result<Update> {
+        let mut index = Index::load(root)?.unwrap_or_else(|| Index {
+            root: root.to_path_buf(),
+            entries: BTreeMap::new(),
+        });
+        let mut update = Update::default();
+        let mut entries = BTreeMap::new();
+
+        for file in walk::collect_files(&[root.to_path_buf()])? {
+            let relative = file.strip_prefix(root).unwrap_or(&file).to_path_buf();
+            let metadata = fs::metadata(&file)?;
+            let modified = walk::modified(&metadata);
+
+            let entry = match index.entries.remove(&relative) {
+                Some(entry) if entry.modified == modified && entry.len == metadata.len() => entry,
+                _ => {
+                    // 검색할 때와 같이 풀어서 색인. 풀 수 없는 파일은 색인하지 않아 항상 검색
+                    let Ok(contents) = read_decoded(&file) else {
+                        continue;
+                    };
+                    update.indexed += 1;
+                    Entry {
+                        modified,
+                        len: metadata.len(),
+                        trigrams: trigrams(&contents),
+                    }
+                }
+            };
+            entries.insert(relative, entry);
+        }
+
+        update.files = entries.len();
+        // 남은 항목은 지워진 파일
+        update.removed = index.entries.len();
+        index.entries = entries;
+        index.save()?;
+        Ok(update)
+    }
+
+    /// 임시 파일에 쓴 뒤 rename. 쓰는 중에 검색해도 이전 색인이나 새 색인 중 하나를 읽음
+    fn save(&self) -> io::Result<()> {
+        let path = self.root.join(INDEX_FILE);
+        let temp = self
+            .root
+            .join(format!("{INDEX_FILE}.{}.tmp", process::id()));
+
+        let result = (|| {
+            let mut file = OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&temp)?;
+            file.write_all(&encode(&self.entries))?;
+            file.sync_all()?;
+            fs::rename(&temp, &path)
+        })();
+        if result.is_err() {
+            let _ = fs::remove_file(&temp);
+        }
+        result
+    }
+
+    /// file을 읽어 봐야 하는지. 색인에 없거나 색인한 뒤 바뀐 파일은 항상 읽음
+    /// patterns는 검색어별 트라이그램으로, 하나라도 모든 트라이그램이 들어 있으면 후보
+    pub fn is_candidate(&self, file: &Path, patterns: &[Vec<u32>]) -> bool {
+        let Some(entry) = file
+            .strip_prefix(&self.root)
+            .ok()
+            .and_then(|relative| self.entries.get(relative))
+        else {
+            return true;
+        };
+        let Ok(metadata) = fs::metadata(file) else {
+            return true;
+        };
+        if entry.modified != walk::modified(&metadata) || entry.len != metadata.len() {
+            return true;
+        }
+
+        patterns.iter().any(|pattern| {
+            pattern
+                .iter()
+                .all(|trigram| entry.trigrams.binary_search(trigram).is_ok())
+        })
+    }
+}
+
+fn read_decoded(file: &Path) -> io::Result<Vec<u8>> {
+    let mut contents = Vec::new();
+    decode::open(file, Encoding::Auto)?
+        .reader
+        .read_to_end(&mut contents)?;
+    Ok(contents)
+}
+
+/// 검색어의 트라이그램. 3바이트보다 짧아 후보를 줄일 수 없으면 None
+pub fn query_trigrams(query: &str) -> Option<Vec<u32>> {
+    let folded = fold::fold_str(query, true, false);
+    if folded.len() < 3 {
+        return None;
+    }
+    Some(trigrams(folded.as_bytes()))
+}
+
+/// 대소문자를 접은 내용의 트라이그램(정렬, 중복 제거)
+/// 검색과 같이 UTF-8이 아닌 부분은 대체 문자로 바꾼 뒤 나눔
+fn trigrams(contents: &[u8]) -> Vec<u32> {
+    let folded = fold::fold_str(&String::from_utf8_lossy(contents), true, false);
+    let mut trigrams: Vec<u32> = folded
+        .as_bytes()
+        .windows(3)
+        .map(|window| u32::from_be_bytes([0, window[0], window[1], window[2]]))
+        .collect();
+    trigrams.sort_unstable();
+    trigrams.dedup();
+    trigrams
+}
+
+/// MAGIC 뒤에 항목마다 경로 길이, 경로, 수정 시각, 크기, 트라이그램 수, 트라이그램 (모두 little endian)
+fn encode(entries: &BTreeMap<PathBuf, Entry>) -> Vec<u8> {
+    let mut data = MAGIC.to_vec();
+    for (path, entry) in entries {
+        let path = path_bytes(path);
+        data.extend_from_slice(&(path.len() as u32).to_le_bytes());
+        data.extend_from_slice(&path);
+        data.extend_from_slice(&entry.modified.to_le_bytes());
+        data.extend_from_slice(&entry.len.to_le_bytes());
+        data.extend_from_slice(&(entry.trigrams.len() as u32).to_le_bytes());
+        for trigram in &entry.trigrams {
+            data.extend_from_slice(&trigram.to_le_bytes());
+        }
+    }
+    data
+}
+
+fn decode(data: &[u8]) -> Option<BTreeMap<PathBuf, Entry>> {
+    let mut rest = data.strip_prefix(MAGIC)?;
+    let mut take = |len: usize| -> Option<&[u8]> {
+        let (taken, remaining) = rest.split_at_checked(len)?;
+        rest = remaining;
+        Some(taken)
+    };
+
+    let mut entries = BTreeMap::new();
+    while let Some(len) = take(4) {
+        let len = u32::from_le_bytes(len.try_into().ok()?) as usize;
+        let path = path_from_bytes(take(len)?);
+        let modified = u128::from_le_bytes(take(16)?.try_into().ok()?);
+        let file_len = u64::from_le_bytes(take(8)?.try_into().ok()?);
+        let count = u32::from_le_bytes(take(4)?.try_into().ok()?) as usize;
+        let trigrams = take(count.checked_mul(4)?)?
+            .chunks_exact(4)
+            .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+            .collect();
+
+        entries.insert(
+            path,
+            Entry {
+                modified,
+                len: file_len,
+                trigrams,
+            },
+        );
+    }
+    Some(entries)
+}
+
+#[cfg(unix)]
+fn path_bytes(path: &Path) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    path.as_os_str().as_bytes().to_vec()
+}
+
+#[cfg(not(unix))]
+fn path_bytes(path: &Path) -> Vec<u8> {
+    path.to_string_lossy().into_owned().into_bytes()
+}
+
+#[cfg(unix)]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+    PathBuf::from(std::ffi::OsStr::from_bytes(bytes))
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_builds_and_updates_index() {
+        let root = std::env::temp_dir().join(format!("minigrep-index-{}", process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("poem.txt"), "Rust:\nsafe, fast, productive.").unwrap();
+        fs::write(root.join("src/lib.rs"), "pub fn search() {}").unwrap();
+
+        let update = Index::update(&root).unwrap();
+        assert_eq!((2, 2, 0), (update.files, update.indexed, update.removed));
+
+        let index = Index::load(&root).unwrap().unwrap();
+        let rust = vec![query_trigrams("RUST").unwrap()];
+        assert!(index.is_candidate(&root.join("poem.txt"), &rust));
+        assert!(!index.is_candidate(&root.join("src/lib.rs"), &rust));
+        // 검색어 중 하나라도 가능하면 후보
+        let either = vec![query_trigrams("fn search").unwrap(), rust[0].clone()];
+        assert!(index.is_candidate(&root.join("src/lib.rs"), &either));
+        // 색인에 없는 파일은 항상 후보
+        assert!(index.is_candidate(&root.join("new.txt"), &rust));
+
+        // 바뀐 파일만 다시 읽고, 지워진 파일은 뺌
+        fs::remove_file(root.join("poem.txt")).unwrap();
+        fs::write(root.join("src/lib.rs"), "// trust").unwrap();
+        let update = Index::update(&root).unwrap();
+        assert_eq!((1, 1, 1), (update.files, update.indexed, update.removed));
+        let index = Index::load(&root).unwrap().unwrap();
+        assert!(index.is_candidate(&root.join("src/lib.rs"), &rust));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn it_folds_case_in_trigrams() {
+        // 접으면 ß => ss이므로 "strasse"로 찾는 줄도 후보에 남음
+        let trigrams = trigrams("Die Straße".as_bytes());
+        assert!(query_trigrams("STRASSE")
+            .unwrap()
+            .iter()
+            .all(|trigram| trigrams.contains(trigram)));
+        assert_eq!(None, query_trigrams("ab"));
+    }
+
+    #[test]
+    fn it_rejects_broken_index() {
+        let mut entries = BTreeMap::new();
+        entries.insert(
+            PathBuf::from("a.txt"),
+            Entry {
+                modified: 7,
+                len: 3,
+                trigrams: vec![1, 2],
+            },
+        );
+        let data = encode(&entries);
+        assert_eq!(Some(entries), decode(&data));
+        assert_eq!(None, decode(&data[..data.len() - 1]));
+        assert_eq!(None, decode(b"garbage"));
+    }
+}
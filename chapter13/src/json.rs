@@ -0,0 +1,214 @@
+use std::path::Path;
+use std::time::Duration;
+
+use crate::search::{Line, Stats};
+
+/// `{"type":"begin","data":{"path":{"text":"a.txt"}}}`
+pub fn begin(path: &Path) -> String {
+    format!(
+        "{{\"type\":\"begin\",\"data\":{{\"path\":{}}}}}",
+        json_data(&path_bytes(path))
+    )
+}
+
+/// 일치하는 줄은 type이 match, 문맥 줄은 context
+/// submatches의 start / end는 원래 줄 안의 바이트 위치, pattern은 일치한 검색어의 번호
+pub fn line(path: &Path, line: &Line) -> String {
+    let lines = match &line.bytes {
+        Some(bytes) => json_data(bytes),
+        None => json_data(line.text.as_bytes()),
+    };
+    let submatches: Vec<String> = line
+        .spans
+        .iter()
+        .zip(&line.patterns)
+        .map(|(span, pattern)| {
+            format!(
+                "{{\"match\":{},\"start\":{},\"end\":{},\"pattern\":{}}}",
+                json_data(line.text[span.clone()].as_bytes()),
+                raw_offset(line, span.start),
+                raw_offset(line, span.end),
+                pattern
+            )
+        })
+        .collect();
+
+    format!(
+        "{{\"type\":\"{}\",\"data\":{{\"path\":{},\"lines\":{},\"line_number\":{},\"absolute_offset\":{},\"submatches\":[{}]}}}}",
+        if line.is_match { "match" } else { "context" },
+        json_data(&path_bytes(path)),
+        lines,
+        line.line_no,
+        line.byte_offset,
+        submatches.join(",")
+    )
+}
+
+pub fn end(path: &Path, stats: &Stats) -> String {
+    format!(
+        "{{\"type\":\"end\",\"data\":{{\"path\":{},\"stats\":{}}}}}",
+        json_data(&path_bytes(path)),
+        stats_json(stats)
+    )
+}
+
+/// 모든 파일을 검색한 뒤 마지막에 한 번 출력
+pub fn summary(stats: &Stats, elapsed: Duration) -> String {
+    format!(
+        "{{\"type\":\"summary\",\"data\":{{\"elapsed_secs\":{:.6},\"stats\":{}}}}}",
+        elapsed.as_secs_f64(),
+        stats_json(stats)
+    )
+}
+
+/// 대체 문자(U+FFFD, 3바이트)로 바뀐 text 안의 위치를 원래 바이트 위치로 변환
+fn raw_offset(line: &Line, offset: usize) -> usize {
+    let Some(bytes) = &line.bytes else {
+        return offset;
+    };
+
+    let (mut text_pos, mut raw_pos) = (0, 0);
+    for chunk in bytes.utf8_chunks() {
+        let valid = chunk.valid().len();
+        if offset <= text_pos + valid {
+            return raw_pos + offset - text_pos;
+        }
+        text_pos += valid;
+        raw_pos += valid;
+
+        if !chunk.invalid().is_empty() {
+            if offset < text_pos + char::REPLACEMENT_CHARACTER.len_utf8() {
+                return raw_pos;
+            }
+            text_pos += char::REPLACEMENT_CHARACTER.len_utf8();
+            raw_pos += chunk.invalid().len();
+        }
+    }
+    raw_pos
+}
+
+fn stats_json(stats: &Stats) -> String {
+    format!(
+        "{{\"searches\":{},\"searches_with_match\":{},\"bytes_searched\":{},\"matched_lines\":{},\"matches\":{}}}",
+        stats.searches,
+        stats.searches_with_match,
+        stats.bytes_searched,
+        stats.matched_lines,
+        stats.matches
+    )
+}
+
+/// UTF-8이면 `{"text":"..."}`, 아니면 원래 바이트를 잃지 않도록 `{"bytes":"<base64>"}`
+fn json_data(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => format!("{{\"text\":{}}}", json_string(text)),
+        Err(_) => format!("{{\"bytes\":\"{}\"}}", base64(bytes)),
+    }
+}
+
+#[cfg(unix)]
+fn path_bytes(path: &Path) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    path.as_os_str().as_bytes().to_vec()
+}
+
+#[cfg(not(unix))]
+fn path_bytes(path: &Path) -> Vec<u8> {
+    path.to_string_lossy().into_owned().into_bytes()
+}
+
+fn json_string(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len() + 2);
+    escaped.push('"');
+    for ch in input.chars() {
+        match ch {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            ch if (ch as u32) < 0x20 || ch == '\u{7f}' => {
+                escaped.push_str(&format!("\\u{:04x}", ch as u32))
+            }
+            _ => escaped.push(ch),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// 표준 base64 (패딩 포함)
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+
+        for idx in 0..4 {
+            if idx <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * idx) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+#[allow(clippy::single_range_in_vec_init)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_writes_match_events() {
+        let line = Line {
+            line_no: 3,
+            byte_offset: 20,
+            text: String::from("say \"hi\"\thi"),
+            bytes: None,
+            is_match: true,
+            spans: vec![5..7, 9..11],
+            patterns: vec![0, 1],
+        };
+
+        assert_eq!(
+            r#"{"type":"match","data":{"path":{"text":"a.txt"},"lines":{"text":"say \"hi\"\thi"},"line_number":3,"absolute_offset":20,"submatches":[{"match":{"text":"hi"},"start":5,"end":7,"pattern":0},{"match":{"text":"hi"},"start":9,"end":11,"pattern":1}]}}"#,
+            super::line(Path::new("a.txt"), &line)
+        );
+        assert_eq!(
+            r#"{"type":"begin","data":{"path":{"text":"a.txt"}}}"#,
+            begin(Path::new("a.txt"))
+        );
+    }
+
+    #[test]
+    fn it_keeps_non_utf8_bytes() {
+        let line = Line {
+            line_no: 1,
+            byte_offset: 0,
+            text: String::from("\u{fffd} body"),
+            bytes: Some(b"\xff body".to_vec()),
+            is_match: true,
+            spans: vec![4..8],
+            patterns: vec![0],
+        };
+        // 위치는 대체 문자가 아닌 원래 바이트 기준
+        let event = super::line(Path::new("a"), &line);
+        assert!(event.contains(r#""lines":{"bytes":"/yBib2R5"}"#));
+        assert!(event.contains(r#"{"match":{"text":"body"},"start":2,"end":6,"pattern":0}"#));
+
+        assert_eq!(r#"{"bytes":"YWJj/w=="}"#, json_data(b"abc\xff"));
+        assert_eq!("", base64(b""));
+        assert_eq!("TWFu", base64(b"Man"));
+        assert_eq!("TWE=", base64(b"Ma"));
+        assert_eq!(r#"{"text":"\u0001"}"#, json_data(b"\x01"));
+    }
+}
@@ -1,83 +1,548 @@
 // 리팩토링하기
+use std::collections::BTreeMap;
 use std::error::Error;
 use std::fs;
+use std::io::{self, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
 
-pub struct CommandConfig {
-    pub query: String,
-    pub file_path: String,
-    pub ignore_case: bool,
+mod color;
+mod config;
+mod decode;
+mod fold;
+mod fuzzy;
+mod ignore;
+mod index;
+mod json;
+mod matcher;
+mod multi;
+mod printer;
+mod replace;
+mod search;
+mod searcher;
+mod threadpool;
+mod walk;
+mod watch;
+
+pub use color::{Palette, COLORS_ENV};
+pub use config::{help_text, ArgError, ColorChoice, CommandConfig, ConfigError, IndexConfig};
+pub use decode::Encoding;
+pub use fold::Folded;
+pub use matcher::{Boundary, MatchOptions, Matcher};
+pub use printer::{ColorPrinter, JsonPrinter, PlainPrinter, PrintOptions, Printer};
+pub use search::{search_reader, Line, SearchLines, Stats};
+pub use searcher::{CaseMode, Searcher, SearcherBuilder};
+pub use watch::Follow;
+use threadpool::ThreadPool;
+
+pub fn run(config: CommandConfig) -> Result<(), Box<dyn Error>> {
+    // let contents = match fs::read_to_string(&config.file_path) {
+    //     Ok(content) => content,
+    //     Err(e) => return Err(Box::new(e))
+    // };
+
+    // 메타 문자가 없는 검색어는 Matcher가 알아서 문자열 비교로 처리
+    // 검색어가 여러 개면 한 번에 비교하는 Matcher 사용
+    let options = MatchOptions {
+        ignore_case: config.ignore_case,
+        ignore_diacritics: config.ignore_diacritics,
+        regex: config.regex,
+        boundary: if config.line_regexp {
+            Boundary::Line
+        } else if config.word_regexp {
+            Boundary::Word
+        } else {
+            Boundary::Anywhere
+        },
+        fuzzy: config.fuzzy,
+    };
+    let patterns = config.patterns()?;
+    let searcher = Searcher::builder()
+        .matcher(Matcher::build(&patterns, options)?)
+        .invert(config.invert_match)
+        .max_count(config.max_count)
+        .context(config.before_context, config.after_context)
+        .build()?;
+
+    // 파일 하나만 검색할 때는 기존처럼 파일 이름 없이 출력
+    let files = walk::collect_files(&config.file_paths)?;
+    let show_file_name = files.len() > 1 || config.file_paths.iter().any(|path| path.is_dir());
+    let files = narrow_with_index(&config, &patterns, files)?;
+    if config.follow && (files.len() != 1 || config.file_paths[0].is_dir()) {
+        return Err("--follow needs a single file".into());
+    }
+    // 검색하는 동안 바뀐 파일도 놓치지 않도록 검색 전에 기록
+    let snapshot = if config.watch {
+        Some(watch::Snapshot::take(&config.file_paths)?)
+    } else {
+        None
+    };
+
+    let threads = config.threads.unwrap_or_else(|| {
+        thread::available_parallelism().map_or(1, |threads| threads.get())
+    });
+    let search = Arc::new(FileSearch {
+        palette: Palette::for_choice(config.color),
+        config,
+        searcher,
+        show_file_name,
+    });
+
+    let result = search_files(&search, files, threads).and_then(|unreadable| match snapshot {
+        Some(snapshot) => run_watch(&search, threads, snapshot),
+        // grep처럼 나머지 파일은 모두 검색한 뒤 실패로 끝냄
+        None if unreadable > 0 => Err(io::Error::other(format!(
+            "{unreadable} of the files could not be read"
+        ))),
+        None => Ok(()),
+    });
+
+    match result {
+        // 출력을 head 등으로 넘겼다가 파이프가 닫힌 경우는 정상 종료
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => Ok(result?),
+    }
 }
 
-impl CommandConfig {
-    pub fn build( // args를 이터레이터 구현체로 변경
-        mut args: impl Iterator<Item=String>
-    ) -> Result<Self, &'static str> {
-        args.next(); // 첫번째 인자는무시
+/// `minigrep index`. 디렉토리마다 색인을 만들거나 갱신
+pub fn run_index(config: IndexConfig) -> Result<(), Box<dyn Error>> {
+    for dir in &config.dirs {
+        if !dir.is_dir() {
+            return Err(format!("{}: not a directory", dir.display()).into());
+        }
+        let update = index::Index::update(dir)?;
+        println!(
+            "{}: {} files indexed ({} updated, {} removed)",
+            dir.display(),
+            update.files,
+            update.indexed,
+            update.removed
+        );
+    }
+    Ok(())
+}
 
-        // ok_or을 이용하여 Some이 아니면 에러 반환
-        let query = args.next().ok_or("there is no query")?;
+/// 검색할 디렉토리에 색인이 있으면, 검색어의 트라이그램이 없는 파일을 목록에서 뺌
+/// 트라이그램으로 판단할 수 없는 검색(정규식, -v, -L, --fuzzy, 발음 구별 기호 무시, 3바이트 미만 검색어)은 그대로
+/// 색인은 파일을 UTF-8로 읽어 만드므로 --encoding을 지정한 검색도 그대로
+fn narrow_with_index(
+    config: &CommandConfig,
+    patterns: &[String],
+    files: Vec<PathBuf>,
+) -> io::Result<Vec<PathBuf>> {
+    let regex = config.regex && patterns.iter().any(|query| matcher::has_meta_characters(query));
+    if regex
+        || config.invert_match
+        || config.files_without_match
+        || config.ignore_diacritics
+        || config.fuzzy.is_some()
+        || config.encoding != Encoding::Auto
+    {
+        return Ok(files);
+    }
+    let Some(trigrams) = patterns
+        .iter()
+        .map(|query| index::query_trigrams(query))
+        .collect::<Option<Vec<_>>>()
+    else {
+        return Ok(files);
+    };
 
-        let file_path = args.next().ok_or("there is no file_path")?;
-            // Some(file_path) => query,
-            // None => return Err("there is no file_path")
+    let mut indexes = Vec::new();
+    for dir in config.file_paths.iter().filter(|path| path.is_dir()) {
+        indexes.extend(index::Index::load(dir)?);
+    }
+    if indexes.is_empty() {
+        return Ok(files);
+    }
 
-        let ignore_case = Self::parse_ignore_case(args);
+    Ok(files
+        .into_iter()
+        .filter(|file| indexes.iter().all(|index| index.is_candidate(file, &trigrams)))
+        .collect())
+}
 
-        Ok(CommandConfig {
-            query,
-            file_path,
-            ignore_case,
+/// 모든 파일 검색에 공통으로 쓰는 설정. 여러 스레드가 Arc로 공유
+struct FileSearch {
+    config: CommandConfig,
+    searcher: Searcher,
+    palette: Option<Palette>,
+    show_file_name: bool,
+}
+
+/// 결과 앞뒤 테두리와 통계 출력을 맡아 파일별 출력을 이어 붙임
+struct Output<W: Write> {
+    out: W,
+    started: Instant,
+    total: Stats,
+    /// 문맥 묶음 구분선(`--`)을 넣어야 하는지 판단하기 위해 출력한 줄이 있는지 기록
+    printed: bool,
+    /// 열 수 없어 건너뛴 파일 수
+    unreadable: usize,
+}
+
+impl<W: Write> Output<W> {
+    fn begin(search: &FileSearch, mut out: W) -> io::Result<Self> {
+        if !search.config.json {
+            writeln!(out, "+-->\n")?;
+        }
+        Ok(Output {
+            out,
+            started: Instant::now(),
+            total: Stats::default(),
+            printed: false,
+            unreadable: 0,
         })
     }
 
-    /**
-     * IGNORE_CASE 포함 여부를 반환하는 메서드
-     */
-    fn parse_ignore_case(args: impl Iterator<Item=String>) -> bool {
-      let key = String::from("IGNORE_CASE");
+    /// 없는 파일, 권한이 없는 파일은 `경로: 에러`만 출력하고 다음 파일로 넘어감
+    fn skip_unreadable<T>(&mut self, file: &Path, result: io::Result<T>) -> io::Result<Option<T>> {
+        match result {
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    io::ErrorKind::NotFound | io::ErrorKind::PermissionDenied
+                ) =>
+            {
+                // 앞 파일에서 찾은 줄이 에러보다 먼저 보이도록
+                self.out.flush()?;
+                eprintln!("{}: {err}", file.display());
+                self.unreadable += 1;
+                Ok(None)
+            }
+            result => result.map(Some),
+        }
+    }
+
+    /// 다른 스레드에서 버퍼에 모아 둔 파일 하나의 결과를 그대로 출력
+    fn push(&mut self, search: &FileSearch, file: &Path, result: FileResult) -> io::Result<()> {
+        let Some(FileOutput { output, stats }) = self.skip_unreadable(file, result)? else {
+            return Ok(());
+        };
+        if !output.is_empty() {
+            if search.separate() && self.printed {
+                writeln!(self.out, "{}", search.paint_separator("--"))?;
+            }
+            self.out.write_all(&output)?;
+            self.printed = true;
+        }
+        self.total.add(&stats);
+        Ok(())
+    }
 
-      std::env::var(&key).is_ok() || args.collect::<Vec<String>>().contains(&key)
+    /// 열 수 없어 건너뛴 파일 수를 돌려줌
+    fn finish(mut self, search: &FileSearch) -> io::Result<usize> {
+        let elapsed = self.started.elapsed();
+        if search.config.json {
+            writeln!(self.out, "{}", json::summary(&self.total, elapsed))?;
+        } else {
+            writeln!(self.out, "\n+-->")?;
+            if search.config.stats {
+                print_stats(&mut self.out, &self.total, elapsed)?;
+            }
+        }
+        self.out.flush()?;
+        Ok(self.unreadable)
     }
 }
 
-pub fn run(config: CommandConfig) -> Result<(), Box<dyn Error>> {
-    // let contents = match fs::read_to_string(&config.file_path) {
-    //     Ok(content) => content,
-    //     Err(e) => return Err(Box::new(e))
-    // };
+/// 파일 하나를 검색한 결과. 출력은 병렬 검색일 때만 버퍼에 모음
+struct FileOutput {
+    output: Vec<u8>,
+    stats: Stats,
+}
+
+type FileResult = io::Result<FileOutput>;
 
-    let contents = fs::read_to_string(&config.file_path)?;
-    let results = if config.ignore_case {
-        search_insensitive(&config.query, &contents)
+/// 열 수 없어 건너뛴 파일 수를 돌려줌
+fn search_files(search: &Arc<FileSearch>, files: Vec<PathBuf>, threads: usize) -> io::Result<usize> {
+    if threads <= 1 || files.len() <= 1 {
+        run_sequential(search, files)
     } else {
-        search(&config.query, &contents)
-    };
-    print_searched_lines(results);
+        run_parallel(search, files, threads)
+    }
+}
 
-    Ok(())
+/// --watch. 파일 목록을 주기적으로 다시 읽어, 새로 생기거나 바뀐 파일만 다시 검색 (에러가 나야 끝남)
+fn run_watch(search: &Arc<FileSearch>, threads: usize, mut snapshot: watch::Snapshot) -> io::Result<()> {
+    loop {
+        thread::sleep(watch::POLL_INTERVAL);
+        let newer = watch::Snapshot::take(&search.config.file_paths)?;
+        let changed = snapshot.changed(&newer);
+        snapshot = newer;
+        if !changed.is_empty() {
+            search_files(search, changed, threads)?;
+        }
+    }
+}
+
+/// 파일을 순서대로 하나씩 검색하며 바로 출력 (출력을 모아 두지 않으므로 메모리 사용량 일정)
+fn run_sequential(search: &FileSearch, files: Vec<PathBuf>) -> io::Result<usize> {
+    let mut output = Output::begin(search, io::stdout().lock())?;
+    for file in files {
+        let result = warn_undecodable(&file, search.search_file(&file, &mut output.out, output.printed));
+        let Some(stats) = output.skip_unreadable(&file, result)? else {
+            continue;
+        };
+        output.printed |= stats.matched_lines > 0 || stats.context_lines > 0;
+        output.total.add(&stats);
+    }
+    output.finish(search)
+}
+
+/// 풀 수 없는 gzip, 잘못된 UTF-16 등 내용을 읽을 수 없는 파일은 경고만 출력하고 다음 파일로 넘어감
+/// 그 파일에서 이미 찾은 줄은 그대로 출력
+fn warn_undecodable(file: &Path, result: io::Result<Stats>) -> io::Result<Stats> {
+    match result {
+        Err(err) if err.kind() == io::ErrorKind::InvalidData => {
+            eprintln!("warning: {}: {err}", file.display());
+            Ok(Stats::default())
+        }
+        result => result,
+    }
 }
 
-fn print_searched_lines(results: Vec<&str>) {
-    println!("+-->\n");
-    for (idx, result) in results.iter().enumerate() {
-        println!("[{idx}]: {result}");
+/// 스레드 풀에서 파일마다 결과를 버퍼에 모은 뒤, 한 파일의 출력이 섞이지 않도록 통째로 출력
+/// sort_files면 끝난 순서와 관계 없이 경로 순서대로 출력
+fn run_parallel(search: &Arc<FileSearch>, files: Vec<PathBuf>, threads: usize) -> io::Result<usize> {
+    let pool = ThreadPool::new(threads.min(files.len()));
+    let (sender, receiver) = mpsc::channel();
+
+    let count = files.len();
+    for (idx, file) in files.into_iter().enumerate() {
+        let search = Arc::clone(search);
+        let sender = sender.clone();
+        pool.execute(move || {
+            // 패닉해도 결과를 보내야 받는 쪽이 이 파일을 기다리다 멈추거나 뒤 파일을 버리지 않음
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                let mut output = Vec::new();
+                warn_undecodable(&file, search.search_file(&file, &mut output, false))
+                    .map(|stats| FileOutput { output, stats })
+            }))
+            .unwrap_or_else(|_| {
+                Err(io::Error::other(format!("{}: search panicked", file.display())))
+            });
+            // 앞의 파일에서 에러가 나 받는 쪽이 먼저 끝났다면 결과는 버림
+            let _ = sender.send((idx, file, result));
+        });
     }
-    println!("\n+-->");
+    drop(sender);
+
+    let mut output = Output::begin(search, io::stdout().lock())?;
+    // 경로 순서대로 출력할 때 먼저 끝난 뒤쪽 파일의 결과를 잠시 보관
+    let mut waiting = BTreeMap::new();
+    let mut next = 0;
+    for (idx, file, result) in receiver.iter().take(count) {
+        if !search.config.sort_files {
+            output.push(search, &file, result)?;
+            continue;
+        }
+
+        waiting.insert(idx, (file, result));
+        while let Some((file, result)) = waiting.remove(&next) {
+            output.push(search, &file, result)?;
+            next += 1;
+        }
+    }
+    output.finish(search)
 }
 
-fn search<'a>(query: &str, contents: &'a str) -> Vec<&'a str> {
+impl FileSearch {
+    fn separate(&self) -> bool {
+        !self.config.json && (self.config.before_context > 0 || self.config.after_context > 0)
+    }
+
+    fn paint_separator(&self, text: &str) -> String {
+        match &self.palette {
+            Some(palette) => Palette::paint(&palette.separator, text),
+            None => text.to_string(),
+        }
+    }
+
+    /// 파일 하나를 검색해 out에 출력하고 통계 반환
+    /// printed는 앞에서 출력한 줄이 있는지 여부(문맥 구분선용)
+    fn search_file(&self, file: &Path, out: &mut impl Write, printed: bool) -> io::Result<Stats> {
+        let config = &self.config;
+
+        // 경로 `-`는 표준 입력. gzip, UTF-16 등은 UTF-8로 풀어서 읽음
+        let (name, decoded) = if file == Path::new("-") {
+            let stdin = decode::decode(io::stdin(), config.encoding)?;
+            (Path::new("(standard input)"), stdin)
+        } else if config.follow {
+            // 끝나지 않는 입력. 아직 비어 있을 수 있으므로 바이너리 검사는 하지 않음
+            (file, decode::decode(watch::Follow::open(file)?, config.encoding)?)
+        } else {
+            let mut decoded = decode::open(file, config.encoding)?;
+            // 디렉토리 탐색 중 만난 바이너리 파일은 건너뜀
+            if decoded.is_binary()? {
+                return Ok(Stats::default());
+            }
+            (file, decoded)
+        };
+        let file_name = if self.show_file_name { Some(name) } else { None };
+
+        if config.in_place {
+            // 압축하거나 다른 인코딩으로 된 파일은 같은 형식으로 다시 쓸 수 없음
+            if !decoded.is_plain() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "cannot rewrite compressed or non-UTF-8 file in place",
+                ));
+            }
+            return self.replace_file(file, out);
+        }
+
+        let mut lines = self.searcher.search(decoded.reader);
+
+        if config.files_with_matches || config.files_without_match {
+            // 첫 줄을 찾으면 파일의 나머지는 읽지 않음
+            lines = lines.context(0, 0).max_count(Some(1));
+            let found = lines.next().transpose()?.is_some();
+            if found == config.files_with_matches {
+                // 파일이 하나여도 -l / -L은 항상 파일 이름 출력
+                let name = name.display().to_string();
+                match &self.palette {
+                    Some(palette) => writeln!(out, "{}", Palette::paint(&palette.path, &name))?,
+                    None => writeln!(out, "{name}")?,
+                }
+            }
+        } else if config.count {
+            lines = lines.context(0, 0);
+            for line in lines.by_ref() {
+                line?;
+            }
+            let count = lines.stats().matched_lines;
+            match file_name {
+                Some(file) => writeln!(out, "{}: {count}", file.display())?,
+                None => writeln!(out, "{count}")?,
+            }
+        } else {
+            let mut printer = self.printer(printed);
+            printer.begin(out, name)?;
+            if config.fuzzy.is_some() {
+                // 파일 안에서 찾은 줄을 모두 모아 편집 거리가 가까운 순서로 출력 (거리가 같으면 줄 순서)
+                let mut found = lines.by_ref().collect::<io::Result<Vec<Line>>>()?;
+                found.sort_by_cached_key(|line| self.searcher.matcher().distance(&line.text));
+                for line in found {
+                    printer.line(out, name, &self.replaced(line))?;
+                }
+            } else {
+                for line in lines.by_ref() {
+                    printer.line(out, name, &self.replaced(line?))?;
+                }
+            }
+            printer.end(out, name, &lines.stats())?;
+        }
+
+        Ok(lines.stats())
+    }
+
+    /// 파일 전체를 읽어 일치한 부분을 바꾼 뒤 다시 씀. dry_run이면 diff만 출력
+    fn replace_file(&self, file: &Path, out: &mut impl Write) -> io::Result<Stats> {
+        let config = &self.config;
+        let contents = fs::read(file)?;
+        let replacement = config.replace.as_deref().unwrap_or_default();
+        let replaced = replace::replace_contents(&contents, self.searcher.matcher(), replacement, config.max_count);
+
+        if !replaced.changes.is_empty() {
+            if config.dry_run {
+                write!(out, "{}", replace::diff(file, &replaced.changes))?;
+            } else {
+                replace::write_atomic(file, &replaced.contents, config.backup_suffix.as_deref())?;
+                writeln!(out, "{}: {} lines replaced", file.display(), replaced.changes.len())?;
+            }
+        }
+
+        Ok(Stats {
+            searches: 1,
+            searches_with_match: usize::from(replaced.matches > 0),
+            bytes_searched: contents.len() as u64,
+            matched_lines: replaced.changes.len(),
+            matches: replaced.matches,
+            context_lines: 0,
+        })
+    }
+
+    /// --json, 색상 여부에 따라 출력 형식 선택. printed는 앞 파일에서 출력한 줄이 있는지 여부
+    fn printer(&self, printed: bool) -> Box<dyn Printer> {
+        if self.config.json {
+            return Box::new(JsonPrinter::new());
+        }
+        let options = PrintOptions {
+            show_path: self.show_file_name,
+            line_number: self.config.line_number,
+            separate_groups: self.separate(),
+        };
+        match &self.palette {
+            Some(palette) => Box::new(ColorPrinter::new(options, palette.clone()).after_output(printed)),
+            None => Box::new(PlainPrinter::new(options).after_output(printed)),
+        }
+    }
+
+    /// --replace면 일치한 부분을 바꾼 줄. 바꾼 부분이 강조되도록 spans도 바뀐 위치로
+    fn replaced(&self, line: Line) -> Line {
+        match &self.config.replace {
+            Some(replacement) if line.is_match => {
+                let matches: Vec<_> =
+                    line.spans.iter().cloned().zip(line.patterns.iter().copied()).collect();
+                let (text, spans) = self.searcher.matcher().replace(&line.text, &matches, replacement);
+                Line { text, spans, bytes: None, ..line }
+            }
+            _ => line,
+        }
+    }
+}
+
+/// --stats 출력
+fn print_stats(out: &mut impl Write, stats: &Stats, elapsed: Duration) -> io::Result<()> {
+    let secs = elapsed.as_secs_f64();
+    let throughput = if secs > 0.0 {
+        stats.bytes_searched as f64 / secs / (1024.0 * 1024.0)
+    } else {
+        0.0
+    };
+
+    writeln!(
+        out,
+        "{} files searched, {} contained matches",
+        stats.searches, stats.searches_with_match
+    )?;
+    writeln!(
+        out,
+        "{} matched lines, {} matches",
+        stats.matched_lines, stats.matches
+    )?;
+    writeln!(
+        out,
+        "{} bytes searched in {secs:.3}s ({throughput:.1} MiB/s)",
+        stats.bytes_searched
+    )
+}
+
+pub fn search<'a>(query: &str, contents: &'a str) -> Vec<&'a str> {
     contents
     .lines()
     .filter(|it| it.contains(query))
     .collect()
 }
 
-fn search_insensitive<'a>(query: &str, contents: &'a str) -> Vec<&'a str> {
-    let query = query.to_lowercase();
+/// 줄마다 to_lowercase()로 복사본을 만드는 대신 Unicode 대소문자 접기로 비교 (ß와 SS도 같음)
+pub fn search_insensitive<'a>(query: &str, contents: &'a str) -> Vec<&'a str> {
+    let query = Folded::new(query, true, false);
 
     contents
     .lines()
-    .filter(|it| it.to_lowercase().contains(&query))
+    .filter(|it| query.is_match(it))
+    .collect()
+}
+
+pub fn search_with<'a>(matcher: &Matcher, contents: &'a str) -> Vec<&'a str> {
+    contents
+    .lines()
+    .filter(|it| matcher.is_match(it))
     .collect()
 }
 
@@ -108,4 +573,44 @@ Trust me.";
         let result = search_insensitive(query, contents);
         assert_eq!(vec!["Rust:", "Trust me."], result);
     }
+
+    #[test]
+    fn it_work_with_regex() {
+        let matcher = Matcher::new(r"^[RT]\w+:?$|three", false, true).unwrap();
+        let contents = "\
+Rust:
+safe, fast, productive.
+Pick three.
+Trust me.";
+        let result = search_with(&matcher, contents);
+        assert_eq!(vec!["Rust:", "Pick three."], result);
+    }
+
+    #[test]
+    fn it_skips_index_with_explicit_encoding() {
+        let dir = std::env::temp_dir().join(format!("minigrep-narrow-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        // 0 바이트가 적어 BOM 없는 UTF-16으로 판단하지 못하는 내용
+        let text = "각각각각각각각각 hello";
+        let utf16: Vec<u8> = text.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        fs::write(dir.join("u.txt"), utf16).unwrap();
+        index::Index::update(&dir).unwrap();
+
+        let narrow = |args: &[&str]| {
+            let args = ["minigrep"].iter().chain(args).map(|arg| arg.to_string());
+            let config = CommandConfig::build(args).unwrap();
+            let files = walk::collect_files(&config.file_paths).unwrap();
+            narrow_with_index(&config, &config.patterns().unwrap(), files).unwrap()
+        };
+        let path = dir.to_str().unwrap();
+        // 자동으로 판단해 읽은 색인에는 "hel"이 없지만 UTF-16LE로 읽으면 일치하는 파일
+        assert!(narrow(&["hello", path]).is_empty());
+        assert_eq!(
+            vec![dir.join("u.txt")],
+            narrow(&["--encoding=utf-16le", "hello", path])
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
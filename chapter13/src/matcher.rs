@@ -0,0 +1,491 @@
+use std::ops::Range;
+
+use regex::{Regex, RegexBuilder};
+use unicode_normalization::char::is_combining_mark;
+
+use crate::fold::Folded;
+use crate::fuzzy::Fuzzy;
+use crate::multi::Patterns;
+
+/// 검색어를 어떻게 비교할지에 대한 설정
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MatchOptions {
+    pub ignore_case: bool,
+    pub ignore_diacritics: bool,
+    pub regex: bool,
+    pub boundary: Boundary,
+    /// 편집 거리가 이 값 이하인 부분도 일치로 취급 (검색어가 하나일 때만)
+    pub fuzzy: Option<usize>,
+}
+
+/// 일치한 구간의 앞뒤가 어디에 닿아야 하는지
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Boundary {
+    /// 줄 어디서든 (기본값)
+    #[default]
+    Anywhere,
+    /// 앞뒤가 단어 문자가 아님 (-w)
+    Word,
+    /// 줄 전체와 일치 (-x)
+    Line,
+}
+
+impl Boundary {
+    /// 일치 구간이 경계 조건을 만족하는지
+    fn accepts(self, line: &str, span: &Range<usize>) -> bool {
+        match self {
+            Boundary::Anywhere => true,
+            Boundary::Word => {
+                !line[..span.start]
+                    .chars()
+                    .next_back()
+                    .is_some_and(is_word_char)
+                    && !line[span.end..].chars().next().is_some_and(is_word_char)
+            }
+            Boundary::Line => *span == (0..line.len()),
+        }
+    }
+}
+
+/// 한 줄이 검색어와 일치하는지 판단하는 방법
+/// 정규식 메타 문자가 없는 검색어는 정규식 엔진을 거치지 않고 contains로 처리
+pub enum Matcher {
+    Literal(String),
+    /// Unicode 대소문자 접기(ß => ss 등)로 비교하는 검색어
+    Folded(Folded),
+    /// regex 크레이트는 백트래킹 없이 입력 길이에 비례하는 시간 안에 매칭을 보장
+    Regex(Regex),
+    /// -e, -f로 받은 여러 검색어
+    Patterns(Patterns),
+    /// 편집 거리 안에서 비슷한 부분을 찾는 검색어 (--fuzzy)
+    Fuzzy(Fuzzy),
+    /// 다른 Matcher의 일치 중 -w, -x 조건을 만족하는 것만 남김
+    Bounded(Box<Matcher>, Boundary),
+}
+
+impl Matcher {
+    /// 검색어 목록으로 Matcher 생성. 검색어가 하나면 기존 방법 그대로 사용
+    pub fn build(patterns: &[String], options: MatchOptions) -> Result<Self, regex::Error> {
+        // 정규식은 가장 왼쪽 일치만 돌려주므로(`a|ab`는 "ab"에서 "a") 경계 조건은 정규식에 직접 넣음
+        // 단어 경계는 앞뒤 글자만 보는 반쪽 경계(\b{start-half})라 `@foo`처럼 단어 문자가 아닌 글자로 시작해도 됨
+        let word_regex = options.regex
+            && options.boundary == Boundary::Word
+            && patterns.iter().any(|query| has_meta_characters(query));
+        let anchored: Vec<String>;
+        let patterns = if options.regex && options.boundary == Boundary::Line {
+            anchored = patterns
+                .iter()
+                .map(|query| format!("^(?:{query})$"))
+                .collect();
+            &anchored
+        } else if word_regex {
+            anchored = patterns
+                .iter()
+                .map(|query| format!(r"\b{{start-half}}(?:{query})\b{{end-half}}"))
+                .collect();
+            &anchored
+        } else {
+            patterns
+        };
+
+        let matcher = match patterns {
+            [query] if options.fuzzy.is_some() => Matcher::Fuzzy(Fuzzy::new(
+                query,
+                options.fuzzy.unwrap_or_default(),
+                options.ignore_case,
+                options.ignore_diacritics,
+            )),
+            [query] if options.ignore_diacritics => {
+                Self::ignoring_diacritics(query, options.ignore_case)
+            }
+            [query] => Self::new(query, options.ignore_case, options.regex)?,
+            _ if options.regex && patterns.iter().any(|query| has_meta_characters(query)) => {
+                Matcher::Patterns(Patterns::regex(patterns, options.ignore_case)?)
+            }
+            _ => Matcher::Patterns(Patterns::literal(
+                patterns,
+                options.ignore_case,
+                options.ignore_diacritics,
+            )),
+        };
+
+        Ok(match options.boundary {
+            Boundary::Anywhere => matcher,
+            Boundary::Word if word_regex => matcher,
+            boundary => Matcher::Bounded(Box::new(matcher), boundary),
+        })
+    }
+
+    pub fn new(query: &str, ignore_case: bool, use_regex: bool) -> Result<Self, regex::Error> {
+        if use_regex && has_meta_characters(query) {
+            let regex = RegexBuilder::new(query)
+                .case_insensitive(ignore_case)
+                .build()?;
+            return Ok(Matcher::Regex(regex));
+        }
+
+        Ok(if ignore_case {
+            Matcher::Folded(Folded::new(query, true, false))
+        } else {
+            Matcher::Literal(query.to_string())
+        })
+    }
+
+    /// 발음 구별 기호(é, ü 등)를 무시하고 비교하는 문자열 검색어. 정규식은 지원하지 않음
+    pub fn ignoring_diacritics(query: &str, ignore_case: bool) -> Self {
+        Matcher::Folded(Folded::new(query, ignore_case, true))
+    }
+
+    pub fn is_match(&self, line: &str) -> bool {
+        match self {
+            Matcher::Literal(query) => line.contains(query.as_str()),
+            Matcher::Folded(folded) => folded.is_match(line),
+            Matcher::Regex(regex) => regex.is_match(line),
+            Matcher::Patterns(patterns) => patterns.is_match(line),
+            Matcher::Fuzzy(fuzzy) => fuzzy.is_match(line),
+            // 빈 검색어는 -x에서 빈 줄과 일치 (빈 구간은 find_matches에서 빠지므로 따로 처리)
+            Matcher::Bounded(inner, Boundary::Line) if line.is_empty() => inner.is_match(line),
+            // 경계 조건과 상관없이 일치하지 않는 줄은 구간을 찾지 않고 바로 제외
+            Matcher::Bounded(inner, _) => inner.is_match(line) && !self.find_spans(line).is_empty(),
+        }
+    }
+
+    /// 줄 안에서 일치하는 모든 구간(바이트 위치). 빈 문자열과의 일치는 제외
+    pub fn find_spans(&self, line: &str) -> Vec<Range<usize>> {
+        match self {
+            Matcher::Literal(query) if query.is_empty() => Vec::new(),
+            Matcher::Literal(query) => line
+                .match_indices(query.as_str())
+                .map(|(start, matched)| start..start + matched.len())
+                .collect(),
+            Matcher::Folded(folded) => folded.find_spans(line),
+            Matcher::Regex(regex) => regex
+                .find_iter(line)
+                .filter(|found| !found.is_empty())
+                .map(|found| found.range())
+                .collect(),
+            Matcher::Patterns(patterns) => patterns
+                .find_matches(line)
+                .into_iter()
+                .map(|(span, _)| span)
+                .collect(),
+            Matcher::Fuzzy(fuzzy) => fuzzy
+                .find(line)
+                .map(|(span, _)| span)
+                .filter(|span| !span.is_empty())
+                .into_iter()
+                .collect(),
+            Matcher::Bounded(..) => self
+                .find_matches(line)
+                .into_iter()
+                .map(|(span, _)| span)
+                .collect(),
+        }
+    }
+
+    /// find_spans와 같되, 각 구간과 일치한 검색어의 번호도 함께. 검색어가 하나면 항상 0
+    pub fn find_matches(&self, line: &str) -> Vec<(Range<usize>, usize)> {
+        match self {
+            Matcher::Patterns(patterns) => patterns.find_matches(line),
+            Matcher::Bounded(inner, Boundary::Line) => {
+                let mut matches = inner.find_matches(line);
+                matches.retain(|(span, _)| Boundary::Line.accepts(line, span));
+                matches
+            }
+            Matcher::Bounded(inner, boundary) => {
+                inner.find_accepted(line, &|span| boundary.accepts(line, span))
+            }
+            _ => self
+                .find_spans(line)
+                .into_iter()
+                .map(|span| (span, 0))
+                .collect(),
+        }
+    }
+
+    /// find_matches와 같되 accept를 만족하는 구간만. 경계에 맞지 않는 후보가 나오면 그 다음 글자부터
+    /// 다시 찾음 ("a-a"를 -w로 "ba-a-a"에서 찾을 때 1..4를 버리고 겹치는 3..6도 확인)
+    fn find_accepted(
+        &self,
+        line: &str,
+        accept: &dyn Fn(&Range<usize>) -> bool,
+    ) -> Vec<(Range<usize>, usize)> {
+        match self {
+            Matcher::Literal(query) if query.is_empty() => Vec::new(),
+            Matcher::Literal(query) => {
+                let mut matches = Vec::new();
+                let mut pos = 0;
+                while let Some(start) = line[pos..].find(query.as_str()) {
+                    let span = pos + start..pos + start + query.len();
+                    if accept(&span) {
+                        pos = span.end;
+                        matches.push((span, 0));
+                    } else {
+                        pos = next_char(line, span.start);
+                    }
+                }
+                matches
+            }
+            Matcher::Folded(folded) => folded
+                .find_accepted(line, accept)
+                .into_iter()
+                .map(|span| (span, 0))
+                .collect(),
+            Matcher::Patterns(patterns) => patterns.find_accepted(line, accept),
+            // 정규식은 경계 조건을 정규식에 넣고, --fuzzy는 줄에서 가장 가까운 구간 하나만 찾음
+            _ => {
+                let mut matches = self.find_matches(line);
+                matches.retain(|(span, _)| accept(span));
+                matches
+            }
+        }
+    }
+
+    /// 일치한 구간을 replacement로 바꾼 줄과, 바꾼 줄 안에서 바뀐 부분의 구간
+    /// 정규식 검색어면 replacement 안의 `$1`, `${name}`을 캡처 그룹으로 치환 (`$$`는 `$`)
+    pub fn replace(
+        &self,
+        line: &str,
+        matches: &[(Range<usize>, usize)],
+        replacement: &str,
+    ) -> (String, Vec<Range<usize>>) {
+        let mut replaced = String::with_capacity(line.len());
+        let mut spans = Vec::with_capacity(matches.len());
+        let mut last = 0;
+        for (span, pattern) in matches {
+            replaced.push_str(&line[last..span.start]);
+            let start = replaced.len();
+            // 같은 정규식으로 같은 위치에서 다시 찾으므로 find_matches와 같은 구간의 캡처를 얻음
+            match self
+                .regex_for(*pattern)
+                .and_then(|regex| regex.captures_at(line, span.start))
+            {
+                Some(captures) => captures.expand(replacement, &mut replaced),
+                None => replaced.push_str(replacement),
+            }
+            spans.push(start..replaced.len());
+            last = span.end;
+        }
+        replaced.push_str(&line[last..]);
+        (replaced, spans)
+    }
+
+    /// --fuzzy일 때 줄에서 가장 가까운 부분까지의 편집 거리. 그 밖에는 None
+    pub fn distance(&self, line: &str) -> Option<usize> {
+        match self {
+            Matcher::Fuzzy(fuzzy) => fuzzy.distance(line),
+            Matcher::Bounded(inner, _) => inner.distance(line),
+            _ => None,
+        }
+    }
+
+    /// pattern번째 검색어가 정규식이면 그 정규식
+    fn regex_for(&self, pattern: usize) -> Option<&Regex> {
+        match self {
+            Matcher::Regex(regex) => Some(regex),
+            Matcher::Patterns(patterns) => patterns.regexes().get(pattern),
+            Matcher::Bounded(inner, _) => inner.regex_for(pattern),
+            Matcher::Literal(_) | Matcher::Folded(_) | Matcher::Fuzzy(_) => None,
+        }
+    }
+}
+
+/// Unicode 글자, 숫자, `_`와 결합 문자(분해된 é의 U+0301 등)는 단어의 일부
+fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_' || is_combining_mark(ch)
+}
+
+/// at에 있는 글자 다음 위치
+pub(crate) fn next_char(text: &str, at: usize) -> usize {
+    at + text[at..].chars().next().map_or(1, char::len_utf8)
+}
+
+/// 이스케이프해도 그대로라면 메타 문자가 없는 순수 문자열
+pub(crate) fn has_meta_characters(query: &str) -> bool {
+    regex::escape(query) != query
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_uses_literal_path_without_meta_characters() {
+        assert!(matches!(
+            Matcher::new("duct", false, true).unwrap(),
+            Matcher::Literal(_)
+        ));
+        assert!(matches!(
+            Matcher::new("du.t", false, true).unwrap(),
+            Matcher::Regex(_)
+        ));
+        // 정규식 모드가 아니면 메타 문자도 글자 그대로 취급
+        assert!(Matcher::new("a.c", false, false).unwrap().is_match("xa.cx"));
+        assert!(!Matcher::new("a.c", false, false).unwrap().is_match("abc"));
+    }
+
+    #[test]
+    fn it_supports_regex_syntax() {
+        let matcher = Matcher::new(r"^(Rust|Pick)\b", false, true).unwrap();
+        assert!(matcher.is_match("Rust:"));
+        assert!(matcher.is_match("Pick three."));
+        assert!(!matcher.is_match("Trust me."));
+
+        let matcher = Matcher::new(r"[0-9]{2,}", false, true).unwrap();
+        assert!(matcher.is_match("port 8080"));
+        assert!(!matcher.is_match("port 8"));
+
+        let matcher = Matcher::new(r"^rust", true, true).unwrap();
+        assert!(matcher.is_match("RUST is fun"));
+    }
+
+    #[test]
+    fn it_finds_match_spans() {
+        let matcher = Matcher::new("ab", false, false).unwrap();
+        assert_eq!(vec![0..2, 3..5], matcher.find_spans("ab ab"));
+
+        // 원래 줄 기준 위치 ('İ'는 접으면 길이가 달라짐)
+        let matcher = Matcher::new("RUST", true, false).unwrap();
+        assert_eq!(vec![4..8], matcher.find_spans("İ: Rust"));
+
+        let matcher = Matcher::new("STRASSE", true, false).unwrap();
+        assert_eq!(vec![4..11], matcher.find_spans("Die Straße"));
+
+        let matcher = Matcher::new(r"\d+|x*", false, true).unwrap();
+        assert_eq!(vec![5..7], matcher.find_spans("port 80"));
+    }
+
+    #[test]
+    fn it_builds_from_pattern_list() {
+        let options = MatchOptions {
+            regex: true,
+            ..MatchOptions::default()
+        };
+        let patterns = vec![String::from("Pick"), String::from(r"\bme")];
+        let matcher = Matcher::build(&patterns, options).unwrap();
+        assert_eq!(vec![(6..8, 1)], matcher.find_matches("Trust me."));
+        assert!(matcher.is_match("Pick three."));
+
+        let matcher = Matcher::build(&patterns[..1], options).unwrap();
+        assert!(matches!(matcher, Matcher::Literal(_)));
+        // -f로 읽은 파일이 비어 있으면 아무 줄과도 일치하지 않음
+        assert!(!Matcher::build(&[], options).unwrap().is_match("Rust:"));
+    }
+
+    #[test]
+    fn it_matches_whole_words_and_lines() {
+        let words = MatchOptions {
+            boundary: Boundary::Word,
+            ..MatchOptions::default()
+        };
+        let matcher = Matcher::build(&[String::from("rust")], words).unwrap();
+        assert!(!matcher.is_match("Trust me, rusty"));
+        assert_eq!(vec![10..14], matcher.find_spans("trust me, rust!"));
+        // 단어 경계는 Unicode 기준
+        assert!(!matcher.is_match("érust"));
+        assert!(matcher.is_match("«rust»"));
+
+        let matcher = Matcher::build(
+            &[String::from("STRASSE")],
+            MatchOptions {
+                ignore_case: true,
+                ..words
+            },
+        )
+        .unwrap();
+        assert_eq!(vec![4..11], matcher.find_spans("Die Straße."));
+        assert!(!matcher.is_match("Die Straßenbahn"));
+
+        let lines = MatchOptions {
+            boundary: Boundary::Line,
+            ..MatchOptions::default()
+        };
+        let matcher = Matcher::build(&[String::from("Rust:")], lines).unwrap();
+        assert!(matcher.is_match("Rust:"));
+        assert!(!matcher.is_match("Rust: fast"));
+        assert!(Matcher::build(&[String::new()], lines)
+            .unwrap()
+            .is_match(""));
+
+        let matcher = Matcher::build(
+            &[String::from("a|ab")],
+            MatchOptions {
+                regex: true,
+                ..lines
+            },
+        )
+        .unwrap();
+        assert!(matcher.is_match("ab"));
+    }
+
+    #[test]
+    fn it_retries_rejected_word_candidates() {
+        // 가장 왼쪽 일치가 단어 경계에 맞지 않아도 같은 위치의 다른 대안을 찾음
+        let regex_words = MatchOptions {
+            regex: true,
+            boundary: Boundary::Word,
+            ..MatchOptions::default()
+        };
+        let matcher = Matcher::build(&[String::from("ab|abc")], regex_words).unwrap();
+        assert!(matcher.is_match("ab"));
+        assert!(matcher.is_match("abc"));
+        assert!(!matcher.is_match("abcd"));
+        assert_eq!(vec![5..8], matcher.find_spans("abcd abc"));
+        let patterns = [String::from("ab"), String::from(r"ab\w")];
+        assert_eq!(
+            vec![(0..3, 1)],
+            Matcher::build(&patterns, regex_words)
+                .unwrap()
+                .find_matches("abc")
+        );
+        // 단어 문자가 아닌 글자로 시작해도 앞 글자만 확인
+        let matcher = Matcher::build(&[String::from("@[a-z]+")], regex_words).unwrap();
+        assert_eq!(vec![5..9], matcher.find_spans("mail @bob."));
+
+        let words = MatchOptions {
+            boundary: Boundary::Word,
+            ..MatchOptions::default()
+        };
+        // 문자열 검색어는 버린 후보와 겹치는 위치부터 다시 찾음
+        let matcher = Matcher::build(&[String::from("a-a")], words).unwrap();
+        assert_eq!(vec![3..6], matcher.find_spans("ba-a-a"));
+        assert!(matcher.is_match("ba-a-a"));
+        assert!(!matcher.is_match("ba-ab"));
+
+        // 버릴 후보가 많은 긴 줄도 줄 길이에 비례하는 시간 안에 끝남
+        let line = "ab".repeat(100_000);
+        for options in [
+            words,
+            MatchOptions {
+                ignore_case: true,
+                ..words
+            },
+        ] {
+            let matcher = Matcher::build(&[String::from("ab")], options).unwrap();
+            assert!(matcher.find_spans(&line).is_empty());
+            let patterns = [String::from("ab"), String::from("ba")];
+            let matcher = Matcher::build(&patterns, options).unwrap();
+            assert!(matcher.find_spans(&format!("é{line}")).is_empty());
+        }
+    }
+
+    #[test]
+    fn it_replaces_matches() {
+        let matcher = Matcher::new(r"(\w+)@(?P<host>\w+)", false, true).unwrap();
+        let line = "mail bob@example or amy@test";
+        let (replaced, spans) = matcher.replace(line, &matcher.find_matches(line), "${host}:$1 $$");
+        assert_eq!("mail example:bob $ or test:amy $", replaced);
+        assert_eq!(vec![5..18, 22..32], spans);
+
+        // 문자열 검색어는 `$`도 글자 그대로
+        let matcher = Matcher::new("RUST", true, false).unwrap();
+        let line = "Rust: trust";
+        let (replaced, _) = matcher.replace(line, &matcher.find_matches(line), "$1");
+        assert_eq!("$1: t$1", replaced);
+    }
+
+    #[test]
+    fn it_reports_invalid_regex() {
+        assert!(Matcher::new("(unclosed", false, true).is_err());
+    }
+}
@@ -0,0 +1,246 @@
+use std::ops::Range;
+
+use aho_corasick::{AhoCorasick, AhoCorasickBuilder, Input, MatchKind};
+use regex::{Regex, RegexBuilder, RegexSet, RegexSetBuilder};
+
+use crate::fold;
+use crate::matcher::next_char;
+
+/// 여러 검색어(-e, -f) 중 하나라도 일치하는지 한 번에 판단
+/// 문자열 검색어는 Aho-Corasick 오토마톤으로 줄을 한 번만 훑고,
+/// 정규식은 RegexSet으로 일치 여부를 먼저 확인한 뒤 일치한 정규식만 위치를 찾는다.
+pub enum Patterns {
+    Literal {
+        automaton: AhoCorasick,
+        fold_case: bool,
+        strip_diacritics: bool,
+    },
+    Regex {
+        set: RegexSet,
+        regexes: Vec<Regex>,
+    },
+}
+
+impl Patterns {
+    pub fn literal(patterns: &[String], fold_case: bool, strip_diacritics: bool) -> Self {
+        // 검색어도 줄과 같은 방법으로 접어 둠. 접은 결과는 소문자이므로 ASCII 줄은 접지 않고
+        // 오토마톤의 ASCII 대소문자 무시 옵션으로 바로 비교할 수 있음
+        let folded: Vec<String> = patterns
+            .iter()
+            .map(|pattern| fold::fold_str(pattern, fold_case, strip_diacritics))
+            .collect();
+        let automaton = AhoCorasickBuilder::new()
+            .match_kind(MatchKind::LeftmostLongest)
+            .ascii_case_insensitive(fold_case)
+            .build(&folded)
+            .expect("literal patterns always build");
+
+        Patterns::Literal {
+            automaton,
+            fold_case,
+            strip_diacritics,
+        }
+    }
+
+    pub fn regex(patterns: &[String], ignore_case: bool) -> Result<Self, regex::Error> {
+        let set = RegexSetBuilder::new(patterns)
+            .case_insensitive(ignore_case)
+            .build()?;
+        let regexes = patterns
+            .iter()
+            .map(|pattern| RegexBuilder::new(pattern).case_insensitive(ignore_case).build())
+            .collect::<Result<_, _>>()?;
+
+        Ok(Patterns::Regex { set, regexes })
+    }
+
+    pub fn is_match(&self, line: &str) -> bool {
+        match self {
+            Patterns::Literal { automaton, .. } if self.needs_folding(line) => {
+                automaton.is_match(self.fold(line).0.as_str())
+            }
+            Patterns::Literal { automaton, .. } => automaton.is_match(line),
+            Patterns::Regex { set, .. } => set.is_match(line),
+        }
+    }
+
+    /// (일치 구간, 검색어 번호) 목록. 구간은 원래 줄 기준 바이트 위치
+    pub fn find_matches(&self, line: &str) -> Vec<(Range<usize>, usize)> {
+        match self {
+            Patterns::Literal { automaton, .. } => {
+                if !self.needs_folding(line) {
+                    return automaton
+                        .find_iter(line)
+                        .filter(|found| !found.is_empty())
+                        .map(|found| (found.range(), found.pattern().as_usize()))
+                        .collect();
+                }
+
+                let (folded, origins) = self.fold(line);
+                automaton
+                    .find_iter(folded.as_str())
+                    .filter(|found| !found.is_empty())
+                    .map(|found| {
+                        let start = origins[found.start()].start;
+                        let end = origins[found.end() - 1].end;
+                        (start..end, found.pattern().as_usize())
+                    })
+                    .collect()
+            }
+            Patterns::Regex { set, regexes } => {
+                let mut matches: Vec<(Range<usize>, usize)> = set
+                    .matches(line)
+                    .into_iter()
+                    .flat_map(|idx| {
+                        regexes[idx]
+                            .find_iter(line)
+                            .filter(|found| !found.is_empty())
+                            .map(move |found| (found.range(), idx))
+                    })
+                    .collect();
+
+                // 여러 정규식의 결과를 줄 앞쪽부터, 겹치는 구간은 앞의 것만 남김
+                matches.sort_by_key(|(range, idx)| (range.start, *idx));
+                let mut end = 0;
+                matches.retain(|(range, _)| {
+                    let keep = range.start >= end;
+                    if keep {
+                        end = range.end;
+                    }
+                    keep
+                });
+                matches
+            }
+        }
+    }
+
+    /// find_matches와 같되 accept를 만족하는 구간만 남김 (-w)
+    /// 문자열 검색어는 버린 후보의 다음 글자부터 다음 후보 하나만 찾으므로 줄을 한 번 훑는 것과 비슷한 시간
+    pub fn find_accepted(
+        &self,
+        line: &str,
+        accept: &dyn Fn(&Range<usize>) -> bool,
+    ) -> Vec<(Range<usize>, usize)> {
+        let Patterns::Literal { automaton, .. } = self else {
+            let mut matches = self.find_matches(line);
+            matches.retain(|(span, _)| accept(span));
+            return matches;
+        };
+
+        let folded = self.needs_folding(line).then(|| self.fold(line));
+        let haystack = folded.as_ref().map_or(line, |(folded, _)| folded.as_str());
+        let origin = |start: usize, end: usize| match &folded {
+            Some((_, origins)) => origins[start].start..origins[end - 1].end,
+            None => start..end,
+        };
+
+        let mut matches: Vec<(Range<usize>, usize)> = Vec::new();
+        let mut pos = 0;
+        // 빈 검색어는 줄 끝에서도 빈 일치를 찾으므로 줄 끝을 넘으면 멈춤
+        while pos <= haystack.len() {
+            let Some(found) = automaton.find(Input::new(haystack).span(pos..haystack.len())) else {
+                break;
+            };
+            if found.is_empty() {
+                pos = next_char(haystack, found.start());
+                continue;
+            }
+            let span = origin(found.start(), found.end());
+            // ß => ss처럼 한 글자가 여러 글자로 접히면 앞 구간과 같은 글자에서 시작할 수 있음
+            let free = matches
+                .last()
+                .is_none_or(|(last, _)| last.end <= span.start);
+            if free && accept(&span) {
+                matches.push((span, found.pattern().as_usize()));
+                pos = found.end();
+            } else {
+                pos = next_char(haystack, found.start());
+            }
+        }
+        matches
+    }
+
+    /// 정규식 검색어 목록. 문자열 검색어면 비어 있음
+    pub fn regexes(&self) -> &[Regex] {
+        match self {
+            Patterns::Literal { .. } => &[],
+            Patterns::Regex { regexes, .. } => regexes,
+        }
+    }
+
+    /// ASCII 줄은 오토마톤이 직접 대소문자를 무시하므로 접지 않아도 됨
+    fn needs_folding(&self, line: &str) -> bool {
+        match self {
+            Patterns::Literal {
+                fold_case,
+                strip_diacritics,
+                ..
+            } => (*fold_case || *strip_diacritics) && !line.is_ascii(),
+            Patterns::Regex { .. } => false,
+        }
+    }
+
+    /// 접은 줄과, 접은 줄의 각 바이트가 온 원래 글자의 바이트 구간
+    fn fold(&self, line: &str) -> (String, Vec<Range<usize>>) {
+        let Patterns::Literal {
+            fold_case,
+            strip_diacritics,
+            ..
+        } = self
+        else {
+            return (line.to_string(), Vec::new());
+        };
+
+        let mut folded = String::with_capacity(line.len());
+        let mut origins = Vec::with_capacity(line.len());
+        fold::fold_line(line, *fold_case, *strip_diacritics, |ch, origin| {
+            folded.push(ch);
+            origins.resize(folded.len(), origin);
+        });
+        (folded, origins)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns(patterns: &[&str]) -> Vec<String> {
+        patterns.iter().map(|pattern| pattern.to_string()).collect()
+    }
+
+    #[test]
+    fn it_reports_which_literal_matched() {
+        let matcher = Patterns::literal(&patterns(&["fast", "safe", "three"]), false, false);
+
+        assert!(matcher.is_match("Pick three."));
+        assert!(!matcher.is_match("Rust:"));
+        assert_eq!(
+            vec![(0..4, 1), (6..10, 0)],
+            matcher.find_matches("safe, fast, productive.")
+        );
+    }
+
+    #[test]
+    fn it_folds_case_for_every_pattern() {
+        let matcher = Patterns::literal(&patterns(&["STRASSE", "rust"]), true, false);
+
+        assert!(matcher.is_match("RUST"));
+        assert!(matcher.is_match("Die Straße"));
+        assert_eq!(vec![(4..11, 0)], matcher.find_matches("Die Straße"));
+
+        let matcher = Patterns::literal(&patterns(&["cafe"]), true, true);
+        assert_eq!(vec![(2..7, 0)], matcher.find_matches("a Café"));
+    }
+
+    #[test]
+    fn it_matches_multiple_regexes() {
+        let matcher = Patterns::regex(&patterns(&[r"\d+", r"[a-z]+\d"]), false).unwrap();
+
+        assert!(matcher.is_match("port 8080"));
+        assert!(!matcher.is_match("PORT"));
+        // 겹치는 구간은 앞에서 시작한 것만
+        assert_eq!(vec![(0..3, 1), (4..6, 0)], matcher.find_matches("ab1 22"));
+        assert!(Patterns::regex(&patterns(&["ok", "("]), false).is_err());
+    }
+}
@@ -0,0 +1,242 @@
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::color::Palette;
+use crate::json;
+use crate::search::{Line, Stats};
+
+/// 검색 결과를 출력하는 형식. 파일(입력) 하나마다 begin, 찾은 줄마다 line, 끝나면 end를 부름
+/// ```
+/// use std::path::Path;
+/// use chapter13::{PlainPrinter, PrintOptions, Printer, Searcher};
+///
+/// let searcher = Searcher::builder().query("three").build().unwrap();
+/// let mut printer = PlainPrinter::new(PrintOptions { line_number: true, ..PrintOptions::default() });
+/// let mut out = Vec::new();
+///
+/// let path = Path::new("poem.txt");
+/// for line in searcher.search_str("Rust:\nPick three.") {
+///     printer.line(&mut out, path, &line).unwrap();
+/// }
+/// assert_eq!("2:6: Pick three.\n", String::from_utf8(out).unwrap());
+/// ```
+pub trait Printer {
+    fn begin(&mut self, _out: &mut dyn Write, _path: &Path) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn line(&mut self, out: &mut dyn Write, path: &Path, line: &Line) -> io::Result<()>;
+
+    fn end(&mut self, _out: &mut dyn Write, _path: &Path, _stats: &Stats) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// 사람이 읽는 형식(PlainPrinter, ColorPrinter)의 설정
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct PrintOptions {
+    /// 줄 앞에 파일 이름 출력
+    pub show_path: bool,
+    /// `줄:열:` 출력 (열은 첫 일치 위치, 바이트 단위)
+    pub line_number: bool,
+    /// 줄 번호가 이어지지 않는 묶음 사이에 `--` 출력 (문맥을 출력할 때)
+    pub separate_groups: bool,
+}
+
+/// `파일:줄:열: 내용` 형식. 문맥 줄은 `파일-줄- 내용`
+pub struct PlainPrinter {
+    options: PrintOptions,
+    prev_line_no: Option<usize>,
+    printed: bool,
+}
+
+impl PlainPrinter {
+    pub fn new(options: PrintOptions) -> Self {
+        PlainPrinter {
+            options,
+            prev_line_no: None,
+            printed: false,
+        }
+    }
+
+    /// 앞 파일에서 이미 출력한 줄이 있으면 첫 묶음 앞에도 `--` 출력
+    pub fn after_output(mut self, printed: bool) -> Self {
+        self.printed = printed;
+        self
+    }
+
+    /// palette가 있으면 파일 이름, 번호, 구분자, 일치한 부분을 색으로 구분
+    fn write_line(
+        &mut self,
+        out: &mut dyn Write,
+        path: &Path,
+        line: &Line,
+        palette: Option<&Palette>,
+    ) -> io::Result<()> {
+        let paint = |code: fn(&Palette) -> &str, text: &str| match palette {
+            Some(palette) => Palette::paint(code(palette), text),
+            None => text.to_string(),
+        };
+
+        // 줄 번호가 이어지지 않으면 새 묶음. 앞 파일에서 출력한 묶음과도 구분
+        let new_group = match self.prev_line_no {
+            Some(prev) => line.line_no != prev + 1,
+            None => self.printed,
+        };
+        if self.options.separate_groups && new_group {
+            writeln!(out, "{}", paint(|palette| &palette.separator, "--"))?;
+        }
+        self.prev_line_no = Some(line.line_no);
+        self.printed = true;
+
+        let mark = paint(
+            |palette| &palette.separator,
+            if line.is_match { ":" } else { "-" },
+        );
+        let mut location = Vec::new();
+        if self.options.show_path {
+            location.push(paint(|palette| &palette.path, &path.display().to_string()));
+        }
+        if self.options.line_number {
+            location.push(paint(|palette| &palette.line_no, &line.line_no.to_string()));
+            if line.is_match {
+                location.push(paint(|palette| &palette.line_no, &line.column().to_string()));
+            }
+        }
+
+        let text = match palette {
+            Some(palette) => palette.highlight(&line.text, &line.spans),
+            None => line.text.clone(),
+        };
+        if location.is_empty() {
+            writeln!(out, "{text}")
+        } else {
+            writeln!(out, "{}{mark} {text}", location.join(&mark))
+        }
+    }
+}
+
+impl Printer for PlainPrinter {
+    fn line(&mut self, out: &mut dyn Write, path: &Path, line: &Line) -> io::Result<()> {
+        self.write_line(out, path, line, None)
+    }
+}
+
+/// PlainPrinter와 같은 형식에 ANSI 색상을 입힘
+pub struct ColorPrinter {
+    plain: PlainPrinter,
+    palette: Palette,
+}
+
+impl ColorPrinter {
+    pub fn new(options: PrintOptions, palette: Palette) -> Self {
+        ColorPrinter {
+            plain: PlainPrinter::new(options),
+            palette,
+        }
+    }
+
+    pub fn after_output(mut self, printed: bool) -> Self {
+        self.plain = self.plain.after_output(printed);
+        self
+    }
+}
+
+impl Printer for ColorPrinter {
+    fn line(&mut self, out: &mut dyn Write, path: &Path, line: &Line) -> io::Result<()> {
+        self.plain.write_line(out, path, line, Some(&self.palette))
+    }
+}
+
+/// ripgrep의 --json과 같은 begin, match / context, end 이벤트를 한 줄에 하나씩
+#[derive(Default)]
+pub struct JsonPrinter;
+
+impl JsonPrinter {
+    pub fn new() -> Self {
+        JsonPrinter
+    }
+}
+
+impl Printer for JsonPrinter {
+    fn begin(&mut self, out: &mut dyn Write, path: &Path) -> io::Result<()> {
+        writeln!(out, "{}", json::begin(path))
+    }
+
+    fn line(&mut self, out: &mut dyn Write, path: &Path, line: &Line) -> io::Result<()> {
+        writeln!(out, "{}", json::line(path, line))
+    }
+
+    fn end(&mut self, out: &mut dyn Write, path: &Path, stats: &Stats) -> io::Result<()> {
+        writeln!(out, "{}", json::end(path, stats))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::searcher::Searcher;
+
+    const CONTENTS: &str = "Rust:\nsafe, fast, productive.\nPick three.\nTrust me.";
+
+    fn print(printer: &mut dyn Printer, searcher: &Searcher) -> String {
+        let path = Path::new("poem.txt");
+        let mut out = Vec::new();
+        let lines = searcher.search_str(CONTENTS);
+        printer.begin(&mut out, path).unwrap();
+        for line in &lines {
+            printer.line(&mut out, path, line).unwrap();
+        }
+        printer.end(&mut out, path, &Stats::default()).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn it_prints_plain_lines_with_groups() {
+        let searcher = Searcher::builder()
+            .query("fast")
+            .invert(true)
+            .build()
+            .unwrap();
+        let options = PrintOptions {
+            show_path: true,
+            line_number: true,
+            separate_groups: true,
+        };
+        // -v로 찾은 줄은 열 번호 대신 1, 이어지지 않는 줄 사이에 `--`
+        assert_eq!(
+            "poem.txt:1:1: Rust:\n--\npoem.txt:3:1: Pick three.\npoem.txt:4:1: Trust me.\n",
+            print(&mut PlainPrinter::new(options), &searcher)
+        );
+
+        let searcher = Searcher::builder()
+            .query("three")
+            .context(1, 0)
+            .build()
+            .unwrap();
+        assert_eq!(
+            "--\npoem.txt-2- safe, fast, productive.\npoem.txt:3:6: Pick three.\n",
+            print(
+                &mut PlainPrinter::new(options).after_output(true),
+                &searcher
+            )
+        );
+    }
+
+    #[test]
+    fn it_prints_colors_and_json() {
+        let searcher = Searcher::builder().query("three").build().unwrap();
+        let mut printer = ColorPrinter::new(PrintOptions::default(), Palette::default());
+        assert_eq!(
+            "Pick \x1b[01;31mthree\x1b[0m.\n",
+            print(&mut printer, &searcher)
+        );
+
+        let json = print(&mut JsonPrinter::new(), &searcher);
+        let events: Vec<&str> = json.lines().collect();
+        assert_eq!(3, events.len());
+        assert!(events[0].starts_with("{\"type\":\"begin\""));
+        assert!(events[1].starts_with("{\"type\":\"match\""));
+        assert!(events[2].starts_with("{\"type\":\"end\""));
+    }
+}
@@ -0,0 +1,217 @@
+use std::ffi::OsString;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use crate::matcher::Matcher;
+
+/// --in-place로 바뀐 줄 하나
+#[derive(Debug, PartialEq)]
+pub struct Change {
+    pub line_no: usize,
+    pub old: String,
+    pub new: String,
+}
+
+/// 파일 내용의 각 줄에서 일치한 부분을 바꾼 결과
+pub struct Replaced {
+    pub contents: Vec<u8>,
+    pub changes: Vec<Change>,
+    /// 바꾼 구간 수
+    pub matches: usize,
+}
+
+/// 줄 끝(`\n`, `\r\n`)과 UTF-8이 아닌 줄은 그대로 두고, 일치한 부분만 바꿈
+/// max_count개의 줄을 바꾸면 나머지 줄은 그대로 둠
+pub fn replace_contents(
+    contents: &[u8],
+    matcher: &Matcher,
+    replacement: &str,
+    max_count: Option<usize>,
+) -> Replaced {
+    let mut replaced = Replaced {
+        contents: Vec::with_capacity(contents.len()),
+        changes: Vec::new(),
+        matches: 0,
+    };
+
+    for (idx, raw) in contents.split_inclusive(|byte| *byte == b'\n').enumerate() {
+        let body_len = raw.len() - line_ending(raw).len();
+        let text = match std::str::from_utf8(&raw[..body_len]) {
+            Ok(text) if max_count.is_none_or(|max| replaced.changes.len() < max) => text,
+            _ => {
+                replaced.contents.extend_from_slice(raw);
+                continue;
+            }
+        };
+
+        let matches = if matcher.is_match(text) {
+            matcher.find_matches(text)
+        } else {
+            Vec::new()
+        };
+        if matches.is_empty() {
+            replaced.contents.extend_from_slice(raw);
+            continue;
+        }
+
+        let (new, _) = matcher.replace(text, &matches, replacement);
+        replaced.contents.extend_from_slice(new.as_bytes());
+        replaced.contents.extend_from_slice(line_ending(raw));
+        replaced.matches += matches.len();
+        if new != text {
+            replaced.changes.push(Change {
+                line_no: idx + 1,
+                old: text.to_string(),
+                new,
+            });
+        }
+    }
+
+    replaced
+}
+
+fn line_ending(line: &[u8]) -> &[u8] {
+    if line.ends_with(b"\r\n") {
+        &line[line.len() - 2..]
+    } else if line.ends_with(b"\n") {
+        &line[line.len() - 1..]
+    } else {
+        &[]
+    }
+}
+
+/// 같은 디렉토리의 임시 파일에 쓴 뒤 rename으로 교체
+/// 중간에 실패해도 원래 파일은 온전하게 남음. backup_suffix가 있으면 원래 내용을 `파일+접미사`로 복사
+pub fn write_atomic(path: &Path, contents: &[u8], backup_suffix: Option<&str>) -> io::Result<()> {
+    let temp = temp_path(path);
+    let result = (|| {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp)?;
+        file.write_all(contents)?;
+        file.set_permissions(fs::metadata(path)?.permissions())?;
+        file.sync_all()?;
+
+        if let Some(suffix) = backup_suffix {
+            fs::copy(path, with_suffix(path, suffix))?;
+        }
+        fs::rename(&temp, path)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// `dir/.name.minigrep-<pid>.tmp`. rename이 원자적이려면 같은 파일 시스템(같은 디렉토리)이어야 함
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(".");
+    name.push(path.file_name().unwrap_or_default());
+    name.push(format!(".minigrep-{}.tmp", process::id()));
+    path.with_file_name(name)
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// 바뀐 줄마다 hunk 하나인 unified diff (--dry-run)
+pub fn diff(path: &Path, changes: &[Change]) -> String {
+    let path = path.display();
+    let mut diff = format!("--- {path}\n+++ {path}\n");
+    // 바꾼 내용에 줄바꿈이 들어 있으면 뒤쪽 줄 번호가 밀림
+    let mut shift: isize = 0;
+    for change in changes {
+        let new_lines: Vec<&str> = change.new.split('\n').collect();
+        let new_start = change.line_no as isize + shift;
+        diff.push_str(&format!(
+            "@@ -{},1 +{new_start},{} @@\n",
+            change.line_no,
+            new_lines.len()
+        ));
+        diff.push_str(&format!("-{}\n", change.old));
+        for line in &new_lines {
+            diff.push_str(&format!("+{line}\n"));
+        }
+        shift += new_lines.len() as isize - 1;
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_replaces_lines_and_keeps_line_endings() {
+        let matcher = Matcher::new("rust", true, false).unwrap();
+        let contents = b"Rust:\r\nsafe\n\xff rust\nTrust me.";
+
+        let replaced = replace_contents(contents, &matcher, "Go", None);
+        // UTF-8이 아닌 줄은 건드리지 않음
+        assert_eq!(b"Go:\r\nsafe\n\xff rust\nTGo me.".to_vec(), replaced.contents);
+        assert_eq!(2, replaced.matches);
+        assert_eq!(
+            vec![
+                Change {
+                    line_no: 1,
+                    old: String::from("Rust:"),
+                    new: String::from("Go:"),
+                },
+                Change {
+                    line_no: 4,
+                    old: String::from("Trust me."),
+                    new: String::from("TGo me."),
+                },
+            ],
+            replaced.changes
+        );
+
+        let replaced = replace_contents(contents, &matcher, "Go", Some(1));
+        assert_eq!(b"Go:\r\nsafe\n\xff rust\nTrust me.".to_vec(), replaced.contents);
+    }
+
+    #[test]
+    fn it_writes_atomically_with_backup() {
+        let dir = std::env::temp_dir().join(format!("minigrep-replace-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("poem.txt");
+        fs::write(&path, "old").unwrap();
+
+        write_atomic(&path, b"new", Some(".bak")).unwrap();
+        assert_eq!("new", fs::read_to_string(&path).unwrap());
+        assert_eq!("old", fs::read_to_string(dir.join("poem.txt.bak")).unwrap());
+        assert!(!temp_path(&path).exists());
+
+        // 원래 파일이 없으면 실패하고 임시 파일도 남기지 않음
+        assert!(write_atomic(&dir.join("missing"), b"new", None).is_err());
+        assert!(!temp_path(&dir.join("missing")).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn it_formats_diff() {
+        let changes = vec![
+            Change {
+                line_no: 2,
+                old: String::from("a b"),
+                new: String::from("a\nb"),
+            },
+            Change {
+                line_no: 5,
+                old: String::from("c"),
+                new: String::from("d"),
+            },
+        ];
+        assert_eq!(
+            "--- f\n+++ f\n@@ -2,1 +2,2 @@\n-a b\n+a\n+b\n@@ -5,1 +6,1 @@\n-c\n+d\n",
+            diff(Path::new("f"), &changes)
+        );
+    }
+}
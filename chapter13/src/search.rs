@@ -0,0 +1,381 @@
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::io::{self, BufRead};
+use std::ops::Range;
+
+use crate::matcher::Matcher;
+
+/// 출력할 한 줄. is_match가 false면 앞뒤 문맥으로 함께 출력하는 줄
+#[derive(Debug, PartialEq)]
+pub struct Line {
+    /// 1부터 시작하는 줄 번호
+    pub line_no: usize,
+    /// 입력 시작부터 이 줄 첫 바이트까지의 거리
+    pub byte_offset: u64,
+    pub text: String,
+    /// 줄이 UTF-8이 아니라서 text에 대체 문자가 들어갔을 때의 원래 바이트
+    pub bytes: Option<Vec<u8>>,
+    pub is_match: bool,
+    /// text 안에서 검색어와 일치한 구간(바이트 위치)
+    /// 문맥 줄과 -v로 찾은 줄은 비어 있음
+    pub spans: Vec<Range<usize>>,
+    /// spans와 같은 순서로, 각 구간과 일치한 검색어의 번호(-e, -f에서 나온 순서, 0부터)
+    pub patterns: Vec<usize>,
+}
+
+impl Line {
+    /// 첫 일치 구간의 열 번호(1부터, 바이트 단위). 일치 구간이 없으면 1
+    pub fn column(&self) -> usize {
+        self.spans.first().map_or(1, |span| span.start + 1)
+    }
+}
+
+/// 파일 하나(또는 전체)의 검색 통계
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Stats {
+    /// 검색한 파일 수
+    pub searches: usize,
+    /// 일치하는 줄이 하나라도 있던 파일 수
+    pub searches_with_match: usize,
+    pub bytes_searched: u64,
+    pub matched_lines: usize,
+    /// 일치한 구간 수 (한 줄에 여러 번 일치할 수 있음)
+    pub matches: usize,
+    /// 함께 출력한 문맥 줄 수
+    pub context_lines: usize,
+}
+
+impl Stats {
+    pub fn add(&mut self, other: &Stats) {
+        self.searches += other.searches;
+        self.searches_with_match += other.searches_with_match;
+        self.bytes_searched += other.bytes_searched;
+        self.matched_lines += other.matched_lines;
+        self.matches += other.matches;
+        self.context_lines += other.context_lines;
+    }
+}
+
+/// reader에서 한 줄씩 읽어 일치하는 줄(과 문맥 줄)을 돌려주는 이터레이터
+/// 파일 전체를 메모리에 올리지 않으므로, 아주 큰 파일이나 표준 입력도 일정한 메모리로 검색한다.
+/// 문맥 줄 번호가 이어지지 않는 곳이 묶음의 경계.
+pub struct SearchLines<'m, R> {
+    reader: R,
+    matcher: &'m Matcher,
+    invert: bool,
+    before: usize,
+    after: usize,
+    line_no: usize,
+    /// 다음 줄이 시작하는 바이트 위치
+    offset: u64,
+    buf: Vec<u8>,
+    /// 아직 일치하는 줄을 만나지 않은 최근 before개의 줄
+    before_lines: VecDeque<Line>,
+    /// 일치하는 줄 뒤에 더 출력해야 하는 줄 수
+    after_remaining: usize,
+    /// 이만큼 찾으면 남은 문맥 줄만 출력하고 더 읽지 않음
+    max_count: Option<usize>,
+    matches: usize,
+    pending: VecDeque<Line>,
+    done: bool,
+    stats: Stats,
+}
+
+/// 일치하는 줄을 찾는 이터레이터 생성. 문맥은 context로 지정
+pub fn search_reader<R: BufRead>(reader: R, matcher: &Matcher) -> SearchLines<'_, R> {
+    SearchLines {
+        reader,
+        matcher,
+        invert: false,
+        before: 0,
+        after: 0,
+        line_no: 0,
+        offset: 0,
+        buf: Vec::new(),
+        before_lines: VecDeque::new(),
+        after_remaining: 0,
+        max_count: None,
+        matches: 0,
+        pending: VecDeque::new(),
+        done: false,
+        stats: Stats {
+            searches: 1,
+            ..Stats::default()
+        },
+    }
+}
+
+impl<R: BufRead> SearchLines<'_, R> {
+    /// true면 일치하지 않는 줄을 돌려줌
+    pub fn invert(mut self, invert: bool) -> Self {
+        self.invert = invert;
+        self
+    }
+
+    /// 일치하는 줄 앞 before줄, 뒤 after줄도 함께 돌려줌
+    /// 겹치는 구간의 줄은 한 번만 나온다.
+    pub fn context(mut self, before: usize, after: usize) -> Self {
+        self.before = before;
+        self.after = after;
+        self
+    }
+
+    /// max_count개의 줄을 찾으면 검색을 멈춤(뒤 문맥 줄까지만 읽음)
+    /// 파일에 일치하는 줄이 있는지만 알면 될 때 Some(1)로 나머지를 읽지 않을 수 있다.
+    pub fn max_count(mut self, max_count: Option<usize>) -> Self {
+        self.max_count = max_count;
+        self
+    }
+
+    fn reached_max_count(&self) -> bool {
+        self.max_count.is_some_and(|max_count| self.matches >= max_count)
+    }
+
+    /// 지금까지 읽은 바이트 수
+    pub fn bytes_read(&self) -> u64 {
+        self.offset
+    }
+
+    /// 지금까지 돌려준 줄의 통계
+    pub fn stats(&self) -> Stats {
+        Stats {
+            bytes_searched: self.offset,
+            searches_with_match: usize::from(self.stats.matched_lines > 0),
+            ..self.stats
+        }
+    }
+
+    /// 다음 줄을 읽어 줄 끝(\n, \r\n)을 뗀 문자열로 반환. 파일 끝이면 None
+    /// UTF-8이 아닌 바이트는 대체 문자로 바꿔 검색을 멈추지 않고, 원래 바이트를 함께 돌려줌
+    fn read_line(&mut self) -> io::Result<Option<(String, Option<Vec<u8>>)>> {
+        self.buf.clear();
+        let read = self.reader.read_until(b'\n', &mut self.buf)?;
+        if read == 0 {
+            return Ok(None);
+        }
+        self.offset += read as u64;
+
+        if self.buf.ends_with(b"\n") {
+            self.buf.pop();
+            if self.buf.ends_with(b"\r") {
+                self.buf.pop();
+            }
+        }
+        Ok(Some(match String::from_utf8_lossy(&self.buf) {
+            Cow::Borrowed(text) => (text.to_string(), None),
+            Cow::Owned(text) => (text, Some(self.buf.clone())),
+        }))
+    }
+}
+
+impl<R: BufRead> Iterator for SearchLines<'_, R> {
+    type Item = io::Result<Line>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let line = self.next_line();
+        if let Some(Ok(line)) = &line {
+            if line.is_match {
+                self.stats.matched_lines += 1;
+                self.stats.matches += line.spans.len();
+            } else {
+                self.stats.context_lines += 1;
+            }
+        }
+        line
+    }
+}
+
+impl<R: BufRead> SearchLines<'_, R> {
+    fn next_line(&mut self) -> Option<io::Result<Line>> {
+        if let Some(line) = self.pending.pop_front() {
+            return Some(Ok(line));
+        }
+
+        while !self.done {
+            if self.reached_max_count() && self.after_remaining == 0 {
+                self.done = true;
+                break;
+            }
+
+            let byte_offset = self.offset;
+            let (text, bytes) = match self.read_line() {
+                Ok(Some(line)) => line,
+                Ok(None) => {
+                    self.done = true;
+                    break;
+                }
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            };
+            self.line_no += 1;
+            // 최대 개수를 채운 뒤의 줄은 일치해도 뒤 문맥으로만 출력
+            let is_match =
+                !self.reached_max_count() && self.matcher.is_match(&text) != self.invert;
+            let (spans, patterns) = if is_match && !self.invert {
+                self.matcher.find_matches(&text).into_iter().unzip()
+            } else {
+                (Vec::new(), Vec::new())
+            };
+            let line = Line {
+                line_no: self.line_no,
+                byte_offset,
+                text,
+                bytes,
+                is_match,
+                spans,
+                patterns,
+            };
+
+            if is_match {
+                self.matches += 1;
+                self.after_remaining = self.after;
+                self.pending.extend(self.before_lines.drain(..));
+                self.pending.push_back(line);
+                return self.pending.pop_front().map(Ok);
+            }
+            if self.after_remaining > 0 {
+                self.after_remaining -= 1;
+                return Some(Ok(line));
+            }
+            if self.before > 0 {
+                if self.before_lines.len() == self.before {
+                    self.before_lines.pop_front();
+                }
+                self.before_lines.push_back(line);
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::single_range_in_vec_init)]
+mod tests {
+    use super::*;
+
+    fn line_numbers(lines: SearchLines<'_, &[u8]>) -> Vec<(usize, bool)> {
+        lines
+            .map(|line| line.unwrap())
+            .map(|line| (line.line_no, line.is_match))
+            .collect()
+    }
+
+    #[test]
+    fn it_numbers_and_inverts_lines() {
+        let matcher = Matcher::new("rust", true, false).unwrap();
+        let contents = "Rust:\nsafe, fast, productive.\r\nPick three.\nTrust me.";
+
+        let lines: Vec<Line> = search_reader(contents.as_bytes(), &matcher)
+            .map(|line| line.unwrap())
+            .collect();
+        assert_eq!(
+            vec![
+                Line {
+                    line_no: 1,
+                    byte_offset: 0,
+                    text: String::from("Rust:"),
+                    bytes: None,
+                    is_match: true,
+                    spans: vec![0..4],
+                    patterns: vec![0],
+                },
+                Line {
+                    line_no: 4,
+                    byte_offset: 43,
+                    text: String::from("Trust me."),
+                    bytes: None,
+                    is_match: true,
+                    spans: vec![1..5],
+                    patterns: vec![0],
+                },
+            ],
+            lines
+        );
+        assert_eq!(2, lines[1].column());
+
+        let lines: Vec<Line> = search_reader(contents.as_bytes(), &matcher)
+            .invert(true)
+            .map(|line| line.unwrap())
+            .collect();
+        assert_eq!((2, 6), (lines[0].line_no, lines[0].byte_offset));
+        assert_eq!((3, 31), (lines[1].line_no, lines[1].byte_offset));
+        assert!(lines[0].spans.is_empty());
+    }
+
+    #[test]
+    fn it_merges_overlapping_context() {
+        let contents = "a\nmatch\nb\nc\nmatch\nd\ne\nf\nmatch";
+        let matcher = Matcher::new("match", false, false).unwrap();
+
+        // 2, 5번 줄의 구간(1~3, 4~6)은 이어지므로 빠짐없이 한 번씩, 9번 줄은 앞 1줄만
+        let lines = search_reader(contents.as_bytes(), &matcher).context(1, 1);
+        assert_eq!(
+            vec![
+                (1, false),
+                (2, true),
+                (3, false),
+                (4, false),
+                (5, true),
+                (6, false),
+                (8, false),
+                (9, true),
+            ],
+            line_numbers(lines)
+        );
+
+        let lines = search_reader(contents.as_bytes(), &matcher).context(0, 2);
+        assert_eq!(
+            vec![(2, true), (3, false), (4, false), (5, true), (6, false), (7, false), (9, true)],
+            line_numbers(lines)
+        );
+    }
+
+    #[test]
+    fn it_stops_after_max_count() {
+        let contents = "match\nmatch\na\nmatch\nb\nmatch";
+        let matcher = Matcher::new("match", false, false).unwrap();
+
+        let lines = search_reader(contents.as_bytes(), &matcher).max_count(Some(2));
+        assert_eq!(vec![(1, true), (2, true)], line_numbers(lines));
+
+        // 뒤 문맥은 마저 출력하지만 그 안의 일치하는 줄은 문맥 줄로 취급
+        let lines = search_reader(contents.as_bytes(), &matcher)
+            .context(0, 2)
+            .max_count(Some(2));
+        assert_eq!(vec![(1, true), (2, true), (3, false), (4, false)], line_numbers(lines));
+
+        let mut lines = search_reader(contents.as_bytes(), &matcher).max_count(Some(1));
+        assert!(lines.next().is_some());
+        // 첫 줄만 읽고 멈춤
+        assert_eq!(
+            Stats {
+                searches: 1,
+                searches_with_match: 1,
+                bytes_searched: 6,
+                matched_lines: 1,
+                matches: 1,
+                context_lines: 0,
+            },
+            lines.stats()
+        );
+
+        let lines = search_reader(contents.as_bytes(), &matcher).max_count(Some(0));
+        assert!(line_numbers(lines).is_empty());
+    }
+
+    #[test]
+    fn it_replaces_invalid_utf8() {
+        let matcher = Matcher::new("abc", false, false).unwrap();
+        let contents: &[u8] = b"abc\xff\nxyz\n";
+
+        let lines: Vec<Line> = search_reader(contents, &matcher)
+            .map(|line| line.unwrap())
+            .collect();
+        assert_eq!("abc\u{fffd}", lines[0].text);
+        assert_eq!(Some(b"abc\xff".to_vec()), lines[0].bytes);
+        assert_eq!(1, lines.len());
+    }
+}
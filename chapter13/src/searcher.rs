@@ -0,0 +1,227 @@
+use std::io::BufRead;
+
+use crate::matcher::{MatchOptions, Matcher};
+use crate::search::{search_reader, Line, SearchLines};
+
+/// 대소문자를 비교하는 방법
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum CaseMode {
+    #[default]
+    Sensitive,
+    Insensitive,
+    /// 검색어에 대문자가 하나라도 있으면 구분, 없으면 무시
+    Smart,
+}
+
+/// 검색 설정을 모아 Searcher를 만듦
+/// ```
+/// use chapter13::{CaseMode, Searcher};
+///
+/// let searcher = Searcher::builder()
+///     .query("rust")
+///     .case(CaseMode::Smart)
+///     .context(0, 1)
+///     .build()
+///     .unwrap();
+/// let lines = searcher.search_str("Rust:\nsafe, fast, productive.\nPick three.");
+///
+/// assert_eq!(vec![1, 2], lines.iter().map(|line| line.line_no).collect::<Vec<_>>());
+/// assert!(lines[0].is_match && !lines[1].is_match);
+/// ```
+#[derive(Default)]
+pub struct SearcherBuilder {
+    patterns: Vec<String>,
+    case: CaseMode,
+    regex: bool,
+    matcher: Option<Matcher>,
+    invert: bool,
+    before: usize,
+    after: usize,
+    max_count: Option<usize>,
+}
+
+impl SearcherBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 검색어 하나. 이전에 지정한 검색어는 지움
+    pub fn query(mut self, query: &str) -> Self {
+        self.patterns = vec![query.to_string()];
+        self
+    }
+
+    /// 여러 검색어. 하나라도 일치하면 일치하는 줄
+    pub fn patterns<S: AsRef<str>>(mut self, patterns: impl IntoIterator<Item = S>) -> Self {
+        self.patterns = patterns
+            .into_iter()
+            .map(|pattern| pattern.as_ref().to_string())
+            .collect();
+        self
+    }
+
+    pub fn case(mut self, case: CaseMode) -> Self {
+        self.case = case;
+        self
+    }
+
+    /// 검색어를 정규식으로 취급
+    pub fn regex(mut self, regex: bool) -> Self {
+        self.regex = regex;
+        self
+    }
+
+    /// 직접 만든 Matcher 사용. 지정하면 query, case, regex는 무시
+    pub fn matcher(mut self, matcher: Matcher) -> Self {
+        self.matcher = Some(matcher);
+        self
+    }
+
+    /// 일치하는 줄 앞 before줄, 뒤 after줄도 함께 돌려줌
+    pub fn context(mut self, before: usize, after: usize) -> Self {
+        self.before = before;
+        self.after = after;
+        self
+    }
+
+    /// 일치하지 않는 줄을 찾음
+    pub fn invert(mut self, invert: bool) -> Self {
+        self.invert = invert;
+        self
+    }
+
+    /// 입력마다 max_count개의 줄을 찾으면 멈춤
+    pub fn max_count(mut self, max_count: Option<usize>) -> Self {
+        self.max_count = max_count;
+        self
+    }
+
+    /// 정규식이 잘못되었으면 에러
+    pub fn build(self) -> Result<Searcher, regex::Error> {
+        let matcher = match self.matcher {
+            Some(matcher) => matcher,
+            None => {
+                let ignore_case = match self.case {
+                    CaseMode::Sensitive => false,
+                    CaseMode::Insensitive => true,
+                    CaseMode::Smart => !self
+                        .patterns
+                        .iter()
+                        .any(|pattern| pattern.chars().any(char::is_uppercase)),
+                };
+                let options = MatchOptions {
+                    ignore_case,
+                    regex: self.regex,
+                    ..MatchOptions::default()
+                };
+                Matcher::build(&self.patterns, options)?
+            }
+        };
+
+        Ok(Searcher {
+            matcher,
+            invert: self.invert,
+            before: self.before,
+            after: self.after,
+            max_count: self.max_count,
+        })
+    }
+}
+
+/// 어떤 reader에서든 일치하는 줄(Line)을 찾는 검색기. 출력은 하지 않음
+pub struct Searcher {
+    matcher: Matcher,
+    invert: bool,
+    before: usize,
+    after: usize,
+    max_count: Option<usize>,
+}
+
+impl Searcher {
+    pub fn builder() -> SearcherBuilder {
+        SearcherBuilder::new()
+    }
+
+    pub fn matcher(&self) -> &Matcher {
+        &self.matcher
+    }
+
+    /// reader를 한 줄씩 읽으며 일치하는 줄과 문맥 줄을 돌려주는 이터레이터
+    pub fn search<R: BufRead>(&self, reader: R) -> SearchLines<'_, R> {
+        search_reader(reader, &self.matcher)
+            .invert(self.invert)
+            .context(self.before, self.after)
+            .max_count(self.max_count)
+    }
+
+    /// 메모리에 있는 문자열 검색. 읽기 에러가 날 수 없으므로 Vec으로 바로 돌려줌
+    pub fn search_str(&self, text: &str) -> Vec<Line> {
+        self.search(text.as_bytes()).filter_map(Result::ok).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTENTS: &str = "Rust:\nsafe, fast, productive.\nPick three.\nTrust me.";
+
+    fn line_numbers(searcher: &Searcher) -> Vec<usize> {
+        searcher
+            .search_str(CONTENTS)
+            .iter()
+            .map(|line| line.line_no)
+            .collect()
+    }
+
+    #[test]
+    fn it_builds_searchers() {
+        let searcher = Searcher::builder().query("rust").build().unwrap();
+        assert_eq!(vec![4], line_numbers(&searcher));
+
+        let searcher = Searcher::builder()
+            .query("rust")
+            .case(CaseMode::Smart)
+            .build()
+            .unwrap();
+        assert_eq!(vec![1, 4], line_numbers(&searcher));
+        // 대문자가 있으면 구분
+        let searcher = Searcher::builder()
+            .query("Rust")
+            .case(CaseMode::Smart)
+            .build()
+            .unwrap();
+        assert_eq!(vec![1], line_numbers(&searcher));
+
+        let searcher = Searcher::builder()
+            .patterns(["fast", "three"])
+            .invert(true)
+            .build()
+            .unwrap();
+        assert_eq!(vec![1, 4], line_numbers(&searcher));
+
+        let searcher = Searcher::builder()
+            .query("^P")
+            .regex(true)
+            .context(1, 0)
+            .max_count(Some(1))
+            .build()
+            .unwrap();
+        assert_eq!(vec![2, 3], line_numbers(&searcher));
+        assert!(Searcher::builder().query("(").regex(true).build().is_err());
+    }
+
+    #[test]
+    fn it_uses_given_matcher() {
+        let matcher = Matcher::new("three", false, false).unwrap();
+        let searcher = Searcher::builder()
+            .query("ignored")
+            .matcher(matcher)
+            .build()
+            .unwrap();
+
+        let lines = searcher.search_str(CONTENTS);
+        assert_eq!(1, lines.len());
+        assert_eq!(vec![5..10], lines[0].spans);
+    }
+}
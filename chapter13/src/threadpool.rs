@@ -0,0 +1,99 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{mpsc, Arc, Mutex},
+    thread,
+};
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// chapter20의 스레드 풀과 같은 구조
+/// 검색 결과를 표준 출력으로 내보내야 하므로 작업 시작 / 종료 로그는 출력하지 않음
+/// 작업이 패닉해도 Worker는 죽지 않고 다음 작업을 받음
+pub struct ThreadPool {
+    workers: Vec<Worker>,
+    sender: Option<mpsc::Sender<Job>>,
+}
+
+impl ThreadPool {
+    pub fn new(size: usize) -> Self {
+        assert!(size > 0);
+
+        let (sender, receiver) = mpsc::channel();
+
+        // 하나의 receiver를 여러 Worker가 나눠 쓰도록 Arc + Mutex로 감쌈
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = (0..size)
+            .map(|_| Worker::new(Arc::clone(&receiver)))
+            .collect();
+
+        ThreadPool {
+            workers,
+            sender: Some(sender),
+        }
+    }
+
+    pub fn execute<F>(&self, action: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.sender.as_ref().unwrap().send(Box::new(action)).unwrap();
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        // sender를 drop해 채널을 닫으면 각 Worker의 루프가 끝남
+        drop(self.sender.take());
+
+        for worker in &mut self.workers {
+            if let Some(thread) = worker.thread.take() {
+                // 작업의 패닉은 Worker 안에서 잡으므로 join은 실패하지 않음
+                let _ = thread.join();
+            }
+        }
+    }
+}
+
+struct Worker {
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Worker {
+    fn new(receiver: Arc<Mutex<mpsc::Receiver<Job>>>) -> Self {
+        let thread = thread::spawn(move || loop {
+            // lock은 recv 이후 바로 풀리므로 작업을 실행하는 동안 다른 Worker가 다음 작업을 받을 수 있음
+            let message = receiver.lock().unwrap().recv();
+            match message {
+                Ok(job) => {
+                    // 패닉 메시지는 기본 훅이 출력. 결과를 알리는 것은 작업의 몫
+                    let _ = panic::catch_unwind(AssertUnwindSafe(job));
+                }
+                Err(_) => break,
+            }
+        });
+
+        Worker {
+            thread: Some(thread),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_keeps_running_jobs_after_a_panic() {
+        let (sender, receiver) = mpsc::channel();
+        let pool = ThreadPool::new(1);
+        pool.execute(|| panic!("job panicked"));
+        for idx in 0..3 {
+            let sender = sender.clone();
+            pool.execute(move || sender.send(idx).unwrap());
+        }
+        drop(pool);
+        drop(sender);
+
+        assert_eq!(vec![0, 1, 2], receiver.iter().collect::<Vec<_>>());
+    }
+}
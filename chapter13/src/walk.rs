@@ -0,0 +1,177 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::ignore::{self, IgnoreRules};
+use crate::index::INDEX_FILE;
+
+/// 입력받은 경로들을 검색할 파일 목록으로 펼침
+/// - 파일은 그대로 포함(무시 규칙과 관계 없이)
+/// - 디렉토리는 재귀적으로 탐색하며, .gitignore / .ignore 규칙과 숨김 디렉토리(.git 등), 색인 파일은 건너뜀
+/// - 디렉토리 안의 파일은 이름 순으로 정렬해 항상 같은 순서로 출력
+/// - 권한이 없는 등 읽을 수 없는 디렉토리는 경고만 출력하고 건너뜀
+pub fn collect_files(paths: &[PathBuf]) -> io::Result<Vec<PathBuf>> {
+    walk(paths, true)
+}
+
+/// collect_files와 같지만 경고를 출력하지 않음 (--watch처럼 반복해서 탐색할 때)
+pub fn collect_files_quietly(paths: &[PathBuf]) -> io::Result<Vec<PathBuf>> {
+    walk(paths, false)
+}
+
+fn walk(paths: &[PathBuf], warn: bool) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+
+    for path in paths {
+        if path.is_dir() {
+            let mut stack = Vec::new();
+            walk_dir(path, &mut stack, &mut files, warn)?;
+        } else {
+            files.push(path.clone());
+        }
+    }
+
+    Ok(files)
+}
+
+/// 수정 시각(UNIX 시간, 나노초). 알 수 없으면 0
+pub fn modified(metadata: &fs::Metadata) -> u128 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |duration| duration.as_nanos())
+}
+
+fn walk_dir(
+    dir: &Path,
+    stack: &mut Vec<IgnoreRules>,
+    files: &mut Vec<PathBuf>,
+    warn: bool,
+) -> io::Result<()> {
+    let mut entries: Vec<_> = match fs::read_dir(dir).and_then(|entries| entries.collect()) {
+        Ok(entries) => entries,
+        Err(err) => {
+            if warn {
+                eprintln!("warning: {}: {err}", dir.display());
+            }
+            return Ok(());
+        }
+    };
+    entries.sort_by_key(|entry| entry.file_name());
+
+    let loaded = match IgnoreRules::load(dir) {
+        Some(rules) => {
+            stack.push(rules);
+            true
+        }
+        None => false,
+    };
+
+    for entry in entries {
+        let path = entry.path();
+        let file_type = match entry.file_type() {
+            Ok(file_type) => file_type,
+            Err(err) => {
+                if warn {
+                    eprintln!("warning: {}: {err}", path.display());
+                }
+                continue;
+            }
+        };
+        let is_dir = file_type.is_dir();
+
+        if is_dir && entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        if ignore::is_ignored(stack, &path, is_dir) {
+            continue;
+        }
+
+        if is_dir {
+            walk_dir(&path, stack, files, warn)?;
+        } else if entry.file_name().to_string_lossy().starts_with(INDEX_FILE) {
+            // minigrep index가 만든 색인(과 쓰는 중인 임시 파일)은 검색하지 않음
+            continue;
+        } else if file_type.is_file() {
+            files.push(path);
+        }
+    }
+
+    if loaded {
+        stack.pop();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_walks_directories_with_ignore_files() {
+        let root = std::env::temp_dir().join(format!("minigrep-walk-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("src/nested")).unwrap();
+        fs::create_dir_all(root.join("target")).unwrap();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::write(root.join(".gitignore"), "target/\n*.log\n").unwrap();
+        fs::write(root.join("src/nested/.ignore"), "skip.txt\n").unwrap();
+        fs::write(root.join("a.txt"), "a").unwrap();
+        fs::write(root.join(INDEX_FILE), "index").unwrap();
+        fs::write(root.join("debug.log"), "log").unwrap();
+        fs::write(root.join("target/out.txt"), "out").unwrap();
+        fs::write(root.join(".git/config"), "git").unwrap();
+        fs::write(root.join("src/b.txt"), "b").unwrap();
+        fs::write(root.join("src/nested/c.txt"), "c").unwrap();
+        fs::write(root.join("src/nested/skip.txt"), "skip").unwrap();
+
+        let files = collect_files(&[root.clone(), root.join("debug.log")]).unwrap();
+        let names: Vec<_> = files
+            .iter()
+            .map(|path| path.strip_prefix(&root).unwrap().to_path_buf())
+            .collect();
+
+        assert_eq!(
+            vec![
+                PathBuf::from(".gitignore"),
+                PathBuf::from("a.txt"),
+                PathBuf::from("src/b.txt"),
+                PathBuf::from("src/nested/.ignore"),
+                PathBuf::from("src/nested/c.txt"),
+                // 직접 지정한 파일은 무시 규칙과 관계 없이 포함
+                PathBuf::from("debug.log"),
+            ],
+            names
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn it_skips_unreadable_directories() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root =
+            std::env::temp_dir().join(format!("minigrep-walk-denied-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("denied")).unwrap();
+        fs::write(root.join("a.txt"), "a").unwrap();
+        fs::write(root.join("denied/b.txt"), "b").unwrap();
+        fs::set_permissions(root.join("denied"), fs::Permissions::from_mode(0o000)).unwrap();
+
+        // root 권한이면 그대로 읽히므로 읽을 수 없는 경우만 확인
+        let readable = fs::read_dir(root.join("denied")).is_ok();
+        let files = collect_files(std::slice::from_ref(&root));
+        fs::set_permissions(root.join("denied"), fs::Permissions::from_mode(0o755)).unwrap();
+
+        let files = files.unwrap();
+        assert!(files.contains(&root.join("a.txt")));
+        if !readable {
+            assert_eq!(vec![root.join("a.txt")], files);
+        }
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
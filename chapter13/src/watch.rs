@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use crate::walk;
+
+/// --follow, --watch에서 파일이 바뀌었는지 확인하는 간격
+pub const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// `tail -F`처럼 파일 끝에 이르면 내용이 늘어날 때까지 기다리는 reader (--follow)
+/// 파일이 줄어들면(truncate) 처음부터 다시 읽고, 같은 경로에 새 파일이 생기면(로그 로테이션)
+/// 이전 파일의 남은 내용을 읽은 뒤 새 파일로 넘어감. read가 0을 돌려주지 않으므로 끝나지 않음
+/// 하나의 입력으로 이어 읽으므로 줄 번호는 처음으로 돌아가지 않고 계속 늘어남
+pub struct Follow {
+    path: PathBuf,
+    file: File,
+    /// 현재 파일에서 읽은 위치
+    position: u64,
+    poll: Duration,
+}
+
+impl Follow {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Ok(Follow {
+            path: path.to_path_buf(),
+            file: File::open(path)?,
+            position: 0,
+            poll: POLL_INTERVAL,
+        })
+    }
+
+    /// 파일 끝에서 다시 확인할 때까지 기다리는 시간
+    pub fn poll(mut self, poll: Duration) -> Self {
+        self.poll = poll;
+        self
+    }
+
+    /// 경로가 가리키는 파일이 바뀌었으면 새로 열고, 줄어들었으면 처음으로 돌아감
+    /// 로테이션 중이라 경로에 파일이 잠시 없으면 그대로 기다림
+    fn reopen_if_replaced(&mut self) -> io::Result<()> {
+        let Ok(metadata) = fs::metadata(&self.path) else {
+            return Ok(());
+        };
+        if !same_file(&self.file.metadata()?, &metadata) {
+            self.file = File::open(&self.path)?;
+            self.position = 0;
+        } else if metadata.len() < self.position {
+            self.file.seek(SeekFrom::Start(0))?;
+            self.position = 0;
+        }
+        Ok(())
+    }
+}
+
+impl Read for Follow {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            let read = self.file.read(buf)?;
+            if read > 0 {
+                self.position += read as u64;
+                return Ok(read);
+            }
+            self.reopen_if_replaced()?;
+            if self.position == 0 && self.file.metadata()?.len() > 0 {
+                // 새 파일이나 줄어든 파일은 기다리지 않고 바로 읽음
+                continue;
+            }
+            thread::sleep(self.poll);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn same_file(a: &fs::Metadata, b: &fs::Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    (a.dev(), a.ino()) == (b.dev(), b.ino())
+}
+
+/// inode를 알 수 없으면 크기가 줄어든 경우만 감지
+#[cfg(not(unix))]
+fn same_file(_a: &fs::Metadata, _b: &fs::Metadata) -> bool {
+    true
+}
+
+/// --watch에서 비교할, 검색 대상 파일별 수정 시각(나노초)과 크기
+#[derive(Debug, Default, PartialEq)]
+pub struct Snapshot {
+    files: BTreeMap<PathBuf, (u128, u64)>,
+}
+
+impl Snapshot {
+    /// 디렉토리는 검색할 때와 같이 재귀적으로 탐색. 그 사이에 지워진 파일은 빼고 기록
+    pub fn take(paths: &[PathBuf]) -> io::Result<Self> {
+        let mut files = BTreeMap::new();
+        for file in walk::collect_files_quietly(paths)? {
+            if let Ok(metadata) = fs::metadata(&file) {
+                files.insert(file, (walk::modified(&metadata), metadata.len()));
+            }
+        }
+        Ok(Snapshot { files })
+    }
+
+    /// self 이후 새로 생기거나 바뀐 파일 (경로 순서). 지워진 파일은 검색할 내용이 없으므로 제외
+    pub fn changed(&self, newer: &Snapshot) -> Vec<PathBuf> {
+        newer
+            .files
+            .iter()
+            .filter(|(file, state)| self.files.get(*file) != Some(state))
+            .map(|(file, _)| file.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::process;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("minigrep-{name}-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn read_some(follow: &mut Follow) -> String {
+        let mut buf = [0; 64];
+        let read = follow.read(&mut buf).unwrap();
+        String::from_utf8_lossy(&buf[..read]).into_owned()
+    }
+
+    #[test]
+    fn it_follows_truncation_and_rotation() {
+        let dir = temp_dir("follow");
+        let path = dir.join("app.log");
+        fs::write(&path, "one\n").unwrap();
+
+        let mut follow = Follow::open(&path).unwrap().poll(Duration::from_millis(1));
+        assert_eq!("one\n", read_some(&mut follow));
+
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"two\n").unwrap();
+        assert_eq!("two\n", read_some(&mut follow));
+
+        // 줄어들면 처음부터
+        fs::write(&path, "x\n").unwrap();
+        assert_eq!("x\n", read_some(&mut follow));
+
+        // 로테이션: 이전 파일에 남은 내용을 먼저 읽은 뒤 새 파일로
+        file.write_all(b"last\n").unwrap();
+        fs::rename(&path, dir.join("app.log.1")).unwrap();
+        fs::write(&path, "new\n").unwrap();
+        assert_eq!("last\n", read_some(&mut follow));
+        assert_eq!("new\n", read_some(&mut follow));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn it_finds_changed_files() {
+        let dir = temp_dir("watch");
+        fs::write(dir.join("a.txt"), "a").unwrap();
+        fs::write(dir.join("b.txt"), "b").unwrap();
+        let before = Snapshot::take(std::slice::from_ref(&dir)).unwrap();
+        assert!(before.changed(&before).is_empty());
+
+        fs::write(dir.join("a.txt"), "changed").unwrap();
+        fs::write(dir.join("c.txt"), "c").unwrap();
+        fs::remove_file(dir.join("b.txt")).unwrap();
+        let after = Snapshot::take(std::slice::from_ref(&dir)).unwrap();
+        assert_eq!(vec![dir.join("a.txt"), dir.join("c.txt")], before.changed(&after));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
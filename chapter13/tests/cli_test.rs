@@ -0,0 +1,36 @@
+use std::{fs, path::PathBuf, process::Command};
+
+/// 테스트마다 별도 디렉토리에 검색할 파일 생성
+fn file_dir(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("minigrep-cli-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    for (file, contents) in files {
+        fs::write(dir.join(file), contents).unwrap();
+    }
+    dir
+}
+
+#[test]
+fn it_skips_missing_files_and_fails_at_the_end() {
+    let dir = file_dir("missing", &[("a.txt", "hello a\n"), ("c.txt", "hello c\n")]);
+
+    // 순서대로 검색할 때와 병렬로 검색할 때 모두
+    for threads in ["1", "4"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_minigrep"))
+            .args(["-j", threads, "--sort-files", "hello"])
+            .args(["a.txt", "missing.txt", "c.txt"])
+            .current_dir(&dir)
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        assert!(stdout.contains("a.txt: hello a"), "{stdout}");
+        assert!(stdout.contains("c.txt: hello c"), "{stdout}");
+        assert!(stderr.starts_with("missing.txt: "), "{stderr}");
+        assert!(!output.status.success());
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,11 @@
+#!/bin/sh
+# CGI 예제 스크립트: 요청 정보를 그대로 돌려준다.
+echo "Content-Type: text/plain; charset=utf-8"
+echo ""
+echo "Hello from CGI"
+echo "method: $REQUEST_METHOD"
+echo "path info: $PATH_INFO"
+echo "query: $QUERY_STRING"
+if [ -n "$CONTENT_LENGTH" ]; then
+    echo "body: $(head -c "$CONTENT_LENGTH")"
+fi
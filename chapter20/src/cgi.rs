@@ -0,0 +1,398 @@
+use std::{
+    io::{self, Read, Write},
+    path::{Component, Path, PathBuf},
+    process::{Child, Command, ExitStatus, Stdio},
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{request::Request, response::Response};
+
+/// 스크립트 실행 결과를 확인하는 주기
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// url_prefix 아래의 요청을 dir 안의 프로그램으로 처리하는 CGI/1.1 라우트
+/// `/cgi-bin/hello.sh/extra?x=1` => `cgi-bin/hello.sh` 실행, PATH_INFO는 `/extra`
+pub struct Cgi {
+    url_prefix: String,
+    dir: PathBuf,
+    timeout: Duration,
+}
+
+impl Cgi {
+    pub fn new(url_prefix: &str, dir: impl Into<PathBuf>) -> Self {
+        Cgi {
+            url_prefix: url_prefix.trim_end_matches('/').to_string(),
+            dir: dir.into(),
+            timeout: Duration::from_secs(10),
+        }
+    }
+
+    /// 이 시간 안에 끝나지 않은 스크립트는 강제로 종료
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 요청 경로가 이 라우트에 해당하면 (스크립트 경로, PATH_INFO) 반환
+    pub fn route(&self, url_path: &str) -> Option<(PathBuf, String)> {
+        let rest = url_path.strip_prefix(&self.url_prefix)?.strip_prefix('/')?;
+        let (script, path_info) = match rest.find('/') {
+            Some(idx) => (&rest[..idx], &rest[idx..]),
+            None => (rest, ""),
+        };
+
+        // 스크립트 이름은 디렉토리 바로 아래의 파일 하나만 허용
+        let mut components = Path::new(script).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(name)), None) => {
+                Some((self.dir.join(name), path_info.to_string()))
+            }
+            _ => None,
+        }
+    }
+
+    /// 스크립트를 실행하고 표준 출력을 응답으로 변환
+    /// body는 아직 읽지 않은 요청 본문으로, Content-Length 만큼 읽어 stdin으로 넘긴다.
+    pub fn run<R: Read>(&self, req: &Request, body: &mut R) -> Response {
+        let Some((script, path_info)) = self.route(&req.path) else {
+            return error_response("HTTP/1.1 404 NOT FOUND", "no such script");
+        };
+        if !script.is_file() {
+            return error_response("HTTP/1.1 404 NOT FOUND", "no such script");
+        }
+        let content_length = match req.content_length() {
+            Ok(length) => length,
+            Err(err) => return error_response(err.status_line(), &err.to_string()),
+        };
+        // 본문은 스크립트를 실행하기 전에 모두 읽어 둠. 본문을 덜 보내고 연결을 열어 둔 클라이언트를
+        // 기다리느라 스크립트를 종료한 뒤에도 작업 스레드가 멈추지 않도록 (TCP 연결은 읽기 제한 시간이 있음)
+        let mut input = Vec::new();
+        match body.take(content_length).read_to_end(&mut input) {
+            Ok(read) if read as u64 == content_length => {}
+            Ok(_) => return error_response("HTTP/1.1 400 BAD REQUEST", "incomplete request body"),
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                return error_response("HTTP/1.1 408 REQUEST TIMEOUT", "request body timed out")
+            }
+            Err(err) => {
+                return error_response(
+                    "HTTP/1.1 400 BAD REQUEST",
+                    &format!("cannot read request body: {err}"),
+                )
+            }
+        }
+
+        let script_name = format!(
+            "{}/{}",
+            self.url_prefix,
+            script.file_name().unwrap().to_string_lossy()
+        );
+        let mut command = Command::new(&script);
+        command
+            .env_clear()
+            .env("PATH", std::env::var("PATH").unwrap_or_default())
+            .env("GATEWAY_INTERFACE", "CGI/1.1")
+            .env("SERVER_SOFTWARE", "chapter20")
+            .env("SERVER_PROTOCOL", &req.version)
+            .env("SERVER_NAME", server_name(req))
+            .env("SERVER_PORT", server_port(req))
+            .env("REMOTE_ADDR", remote_addr(req))
+            .env("REQUEST_METHOD", &req.method)
+            .env("SCRIPT_NAME", script_name)
+            .env("PATH_INFO", &path_info)
+            .env("QUERY_STRING", &req.query_string)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit());
+        // 스크립트가 띄운 자식 프로세스까지 한 번에 종료할 수 있도록 새 프로세스 그룹으로 실행
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut command, 0);
+        if content_length > 0 {
+            command.env("CONTENT_LENGTH", content_length.to_string());
+        }
+        if let Some(content_type) = req.header("Content-Type") {
+            command.env("CONTENT_TYPE", content_type);
+        }
+        // 나머지 요청 헤더는 HTTP_ 접두어를 붙여 전달
+        // Proxy 헤더는 HTTP_PROXY가 되어 스크립트의 프록시 설정을 바꿀 수 있으므로 제외 (httpoxy)
+        for (name, value) in &req.headers {
+            if name.eq_ignore_ascii_case("Content-Length")
+                || name.eq_ignore_ascii_case("Content-Type")
+                || name.eq_ignore_ascii_case("Proxy")
+            {
+                continue;
+            }
+            let key = format!("HTTP_{}", name.to_ascii_uppercase().replace('-', "_"));
+            command.env(key, value);
+        }
+
+        let mut child = match command.spawn() {
+            Ok(child) => child,
+            Err(err) => {
+                return error_response(
+                    "HTTP/1.1 500 INTERNAL SERVER ERROR",
+                    &format!("cannot execute script: {err}"),
+                )
+            }
+        };
+
+        let stdin = child.stdin.take();
+        let stdout = child.stdout.take();
+        let deadline = Instant::now() + self.timeout;
+
+        // stdout은 스크립트가 띄운 자식 프로세스도 물려받아 계속 열려 있을 수 있으므로
+        // scope 밖의 스레드에서 읽고, 제한 시간이 지나면 기다리지 않고 버림
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut output = Vec::new();
+            if let Some(mut stdout) = stdout {
+                let _ = stdout.read_to_end(&mut output);
+            }
+            let _ = sender.send(output);
+        });
+
+        // stdin 쓰기와 stdout 읽기를 동시에 하지 않으면 파이프 버퍼가 가득 차서 서로 기다릴 수 있음
+        // 본문을 읽지 않는 자식 프로세스가 stdin을 쥐고 있을 수도 있으므로 기다리지 않는 스레드에서 씀
+        if let Some(mut stdin) = stdin {
+            thread::spawn(move || {
+                // 스크립트가 본문을 다 읽지 않고 끝나면 쓰기 에러가 나지만 무시
+                let _ = stdin.write_all(&input);
+            });
+        }
+
+        match wait_for_output(&mut child, deadline, &receiver) {
+            Ok(Some(output)) => parse_output(&output),
+            Ok(None) => error_response("HTTP/1.1 504 GATEWAY TIMEOUT", "script timed out"),
+            Err(err) => error_response(
+                "HTTP/1.1 500 INTERNAL SERVER ERROR",
+                &format!("script failed: {err}"),
+            ),
+        }
+    }
+}
+
+/// deadline 전에 스크립트가 끝나고 출력을 모두 받으면 Some(출력)
+/// 시간이 지나면 프로세스 그룹을 종료하고 None
+fn wait_for_output(
+    child: &mut Child,
+    deadline: Instant,
+    receiver: &mpsc::Receiver<Vec<u8>>,
+) -> io::Result<Option<Vec<u8>>> {
+    let status = wait_until(child, deadline)?;
+    // 스크립트는 끝났어도 자식 프로세스가 stdout을 쥐고 있으면 제한 시간까지만 기다림
+    let output = status.and_then(|_| {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        receiver.recv_timeout(remaining).ok()
+    });
+    if output.is_none() {
+        kill_group(child)?;
+    }
+    Ok(output)
+}
+
+/// deadline 전에 종료되면 Some(status), 시간이 지나면 None
+fn wait_until(child: &mut Child, deadline: Instant) -> io::Result<Option<ExitStatus>> {
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            return Ok(None);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// 스크립트와 그 프로세스 그룹의 모든 프로세스를 종료
+/// 자식이 stdout 파이프를 계속 쥐고 있으면 응답이 그 프로세스가 끝날 때까지 늦어지기 때문
+fn kill_group(child: &mut Child) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        // libc 크레이트 없이 직접 선언. 두 값은 Linux와 macOS에서 같음
+        extern "C" {
+            fn kill(pid: i32, signal: i32) -> i32;
+        }
+        const SIGKILL: i32 = 9;
+        const ESRCH: i32 = 3;
+        // 음수 pid는 프로세스 그룹. process_group(0)으로 그룹 id는 스크립트의 pid
+        let group = child.id() as i32;
+        // SAFETY: kill은 메모리를 건드리지 않는 시스템 호출
+        if unsafe { kill(-group, SIGKILL) } == -1 {
+            let err = io::Error::last_os_error();
+            // ESRCH는 그룹의 프로세스가 모두 이미 끝난 경우
+            if err.raw_os_error() != Some(ESRCH) {
+                // 스크립트는 아래에서 종료하지만, 스크립트가 띄운 프로세스는 남아 있을 수 있음
+                eprintln!("cannot kill CGI process group {group}: {err}");
+            }
+        }
+    }
+    // 이미 끝났으면 에러가 나지만, 그룹은 위에서 종료했으므로 무시
+    let _ = child.kill();
+    // 좀비 프로세스가 남지 않도록 회수
+    child.wait()?;
+    Ok(())
+}
+
+/// 요청을 받은 리스너의 포트. 알 수 없으면(메모리 스트림) Host 헤더의 포트, 그것도 없으면 80
+fn server_port(req: &Request) -> String {
+    if let Some(addr) = req.local_addr {
+        return addr.port().to_string();
+    }
+    req.header("Host")
+        .and_then(|host| split_host(host).1)
+        .map_or_else(|| String::from("80"), |port| port.to_string())
+}
+
+/// 클라이언트 IP 주소. 알 수 없으면 빈 문자열
+fn remote_addr(req: &Request) -> String {
+    req.remote_addr
+        .map(|addr| addr.ip().to_string())
+        .unwrap_or_default()
+}
+
+fn server_name(req: &Request) -> String {
+    req.header("Host")
+        .map(|host| split_host(host).0)
+        .unwrap_or("localhost")
+        .to_string()
+}
+
+/// Host 헤더를 (호스트, 포트)로 나눔. IPv6 주소는 `[::1]:8080`처럼 대괄호로 감싸므로 대괄호까지 호스트
+/// 포트가 숫자가 아니면 포트가 없는 것으로 취급
+fn split_host(host: &str) -> (&str, Option<&str>) {
+    let (name, port) = match host.find(']') {
+        Some(end) if host.starts_with('[') => (&host[..=end], host[end + 1..].strip_prefix(':')),
+        _ => match host.split_once(':') {
+            Some((name, port)) => (name, Some(port)),
+            None => (host, None),
+        },
+    };
+    (name, port.filter(|port| port.parse::<u16>().is_ok()))
+}
+
+/// 스크립트 출력의 헤더 / 본문 분리
+/// `Status` 헤더가 상태 코드를 정하고, `Location`만 있으면 302로 취급
+fn parse_output(output: &[u8]) -> Response {
+    let (head, body) = match find_header_end(output) {
+        Some((head_end, body_start)) => (&output[..head_end], &output[body_start..]),
+        None => {
+            return error_response(
+                "HTTP/1.1 502 BAD GATEWAY",
+                "script returned no header section",
+            )
+        }
+    };
+
+    let head = String::from_utf8_lossy(head);
+    let mut status_line = None;
+    let mut headers = Vec::new();
+
+    for line in head.lines() {
+        let Some((name, value)) = line.split_once(':') else {
+            return error_response("HTTP/1.1 502 BAD GATEWAY", "malformed script header");
+        };
+        let (name, value) = (name.trim(), value.trim());
+
+        if name.eq_ignore_ascii_case("Status") {
+            status_line = Some(format!("HTTP/1.1 {value}"));
+        } else if name.eq_ignore_ascii_case("Content-Length") {
+            // 길이는 응답을 쓸 때 다시 계산
+            continue;
+        } else {
+            headers.push((name.to_string(), value.to_string()));
+        }
+    }
+
+    let has_location = headers
+        .iter()
+        .any(|(name, _)| name.eq_ignore_ascii_case("Location"));
+    let status_line = status_line.unwrap_or_else(|| {
+        if has_location {
+            String::from("HTTP/1.1 302 FOUND")
+        } else {
+            String::from("HTTP/1.1 200 OK")
+        }
+    });
+
+    Response {
+        status_line,
+        headers,
+        body: body.to_vec(),
+        head_only: false,
+    }
+}
+
+/// 헤더 끝(빈 줄)의 위치. CRLF / LF 모두 허용
+fn find_header_end(output: &[u8]) -> Option<(usize, usize)> {
+    let crlf = output
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .map(|pos| (pos, pos + 4));
+    let lf = output
+        .windows(2)
+        .position(|window| window == b"\n\n")
+        .map(|pos| (pos, pos + 2));
+
+    match (crlf, lf) {
+        (Some(crlf), Some(lf)) => Some(if crlf.0 < lf.0 { crlf } else { lf }),
+        (crlf, lf) => crlf.or(lf),
+    }
+}
+
+fn error_response(status_line: &str, message: &str) -> Response {
+    Response::new(status_line, "text/plain", message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_routes_script_and_path_info() {
+        let cgi = Cgi::new("/cgi-bin/", "cgi-bin");
+
+        assert_eq!(
+            Some((PathBuf::from("cgi-bin/hello.sh"), String::from("/a/b"))),
+            cgi.route("/cgi-bin/hello.sh/a/b")
+        );
+        assert_eq!(
+            Some((PathBuf::from("cgi-bin/hello.sh"), String::new())),
+            cgi.route("/cgi-bin/hello.sh")
+        );
+        assert_eq!(None, cgi.route("/cgi-bin/../secret"));
+        assert_eq!(None, cgi.route("/cgi-binx/hello.sh"));
+    }
+
+    #[test]
+    fn it_parses_script_headers() {
+        let response = parse_output(b"Status: 201 Created\nContent-Type: text/plain\n\nbody\n");
+        assert_eq!("HTTP/1.1 201 Created", response.status_line);
+        assert_eq!(b"body\n".to_vec(), response.body);
+
+        let response = parse_output(b"Location: /\r\n\r\n");
+        assert_eq!("HTTP/1.1 302 FOUND", response.status_line);
+
+        let response = parse_output(b"no headers here");
+        assert_eq!("HTTP/1.1 502 BAD GATEWAY", response.status_line);
+    }
+
+    #[test]
+    fn it_splits_host_header() {
+        assert_eq!(
+            ("example.com", Some("8080")),
+            split_host("example.com:8080")
+        );
+        assert_eq!(("example.com", None), split_host("example.com"));
+        assert_eq!(("[::1]", Some("8080")), split_host("[::1]:8080"));
+        assert_eq!(("[::1]", None), split_host("[::1]"));
+        // 숫자가 아닌 포트는 무시
+        assert_eq!(("example.com", None), split_host("example.com:http"));
+        assert_eq!(("[::1]", None), split_host("[::1]:1]"));
+    }
+}
@@ -0,0 +1,514 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, Write},
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// 폼 / 쿼리 스트링 파싱 결과
+/// 같은 이름의 필드가 여러 번 올 수 있으므로 값 목록으로 관리
+#[derive(Debug, Default)]
+pub struct FormData {
+    fields: HashMap<String, Vec<String>>,
+    files: Vec<UploadedFile>,
+}
+
+impl FormData {
+    /// 이름에 해당하는 첫번째 값
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.fields
+            .get(name)
+            .and_then(|values| values.first())
+            .map(|value| value.as_str())
+    }
+
+    /// 이름에 해당하는 모든 값
+    pub fn get_all(&self, name: &str) -> &[String] {
+        self.fields
+            .get(name)
+            .map(|values| &values[..])
+            .unwrap_or(&[])
+    }
+
+    /// 이름에 해당하는 첫번째 업로드 파일
+    pub fn file(&self, name: &str) -> Option<&UploadedFile> {
+        self.files.iter().find(|file| file.field == name)
+    }
+
+    pub fn files(&self) -> &[UploadedFile] {
+        &self.files
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty() && self.files.is_empty()
+    }
+
+    fn push_field(&mut self, name: String, value: String) {
+        self.fields.entry(name).or_default().push(value);
+    }
+}
+
+/// multipart 요청으로 업로드된 파일
+/// 내용은 메모리가 아닌 임시 파일에 저장되며, drop 시점에 임시 파일도 삭제된다.
+#[derive(Debug)]
+pub struct UploadedFile {
+    pub field: String,
+    pub file_name: Option<String>,
+    pub content_type: Option<String>,
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+impl UploadedFile {
+    pub fn read_to_vec(&self) -> io::Result<Vec<u8>> {
+        fs::read(&self.path)
+    }
+}
+
+impl Drop for UploadedFile {
+    fn drop(&mut self) {
+        // 이미 옮겨졌거나 지워진 경우는 무시
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// 요청 본문 크기 제한
+#[derive(Debug, Clone)]
+pub struct FormLimits {
+    /// 본문 전체 크기
+    pub max_body_size: u64,
+    /// 파일이 아닌 일반 필드 하나의 크기
+    pub max_field_size: usize,
+    /// 업로드 파일 하나의 크기
+    pub max_file_size: u64,
+    /// multipart 파트 개수
+    pub max_parts: usize,
+}
+
+impl Default for FormLimits {
+    fn default() -> Self {
+        FormLimits {
+            max_body_size: 10 * 1024 * 1024,
+            max_field_size: 64 * 1024,
+            max_file_size: 8 * 1024 * 1024,
+            max_parts: 64,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum FormError {
+    Io(io::Error),
+    /// 제한을 넘은 항목 이름
+    TooLarge(&'static str),
+    Malformed(&'static str),
+}
+
+impl fmt::Display for FormError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormError::Io(e) => write!(f, "io error: {e}"),
+            FormError::TooLarge(what) => write!(f, "{what} is too large"),
+            FormError::Malformed(what) => write!(f, "malformed form data: {what}"),
+        }
+    }
+}
+
+impl Error for FormError {}
+
+impl From<io::Error> for FormError {
+    fn from(e: io::Error) -> Self {
+        FormError::Io(e)
+    }
+}
+
+/// %XX 형태로 인코딩된 문자열 디코딩
+/// 쿼리 스트링 / urlencoded 본문은 `+`를 공백으로 취급하지만, 경로는 그렇지 않다.
+pub fn percent_decode(input: &str, plus_as_space: bool) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+
+    while idx < bytes.len() {
+        match bytes[idx] {
+            b'%' if idx + 2 < bytes.len() => {
+                match (hex_value(bytes[idx + 1]), hex_value(bytes[idx + 2])) {
+                    (Some(high), Some(low)) => {
+                        decoded.push(high << 4 | low);
+                        idx += 3;
+                        continue;
+                    }
+                    // 잘못된 이스케이프는 그대로 둔다
+                    _ => decoded.push(b'%'),
+                }
+            }
+            b'+' if plus_as_space => decoded.push(b' '),
+            byte => decoded.push(byte),
+        }
+        idx += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// 경로 한 조각(segment)을 URL에 넣을 수 있도록 인코딩
+/// 영문자, 숫자와 `-._~`를 제외한 모든 바이트를 %XX로 바꾼다.
+pub fn percent_encode(input: &str) -> String {
+    let mut encoded = String::with_capacity(input.len());
+
+    for byte in input.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+
+    encoded
+}
+
+fn hex_value(byte: u8) -> Option<u8> {
+    match byte {
+        b'0'..=b'9' => Some(byte - b'0'),
+        b'a'..=b'f' => Some(byte - b'a' + 10),
+        b'A'..=b'F' => Some(byte - b'A' + 10),
+        _ => None,
+    }
+}
+
+/// `a=1&b=2` 형태의 쿼리 스트링 / application/x-www-form-urlencoded 본문 파싱
+pub fn parse_urlencoded(input: &str) -> FormData {
+    let mut form = FormData::default();
+
+    for pair in input.split('&').filter(|pair| !pair.is_empty()) {
+        let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+        form.push_field(percent_decode(name, true), percent_decode(value, true));
+    }
+
+    form
+}
+
+/// multipart/form-data 본문 파싱
+/// 파일 파트는 메모리에 모으지 않고 읽는 즉시 임시 파일로 흘려 보낸다.
+pub fn parse_multipart<R: BufRead>(
+    reader: R,
+    boundary: &str,
+    limits: &FormLimits,
+) -> Result<FormData, FormError> {
+    let mut reader = MultipartReader::new(reader);
+    let delimiter = format!("\r\n--{boundary}");
+    let mut form = FormData::default();
+
+    // 첫 경계 이전의 preamble은 버린다.
+    // 본문이 곧바로 경계로 시작할 수 있으므로 앞에 CRLF를 붙인 것처럼 취급
+    reader.buf.extend_from_slice(b"\r\n");
+    reader.read_until(delimiter.as_bytes(), &mut io::sink(), u64::MAX)?;
+
+    let mut parts = 0;
+    loop {
+        // 경계 직후 `--`이면 본문 끝, CRLF이면 다음 파트
+        let line = reader.read_line(limits.max_field_size)?;
+        if line.starts_with(b"--") {
+            break;
+        }
+        if !line.is_empty() {
+            return Err(FormError::Malformed("boundary"));
+        }
+
+        parts += 1;
+        if parts > limits.max_parts {
+            return Err(FormError::TooLarge("number of parts"));
+        }
+
+        let headers = reader.read_part_headers(limits.max_field_size)?;
+        let name = headers
+            .name
+            .ok_or(FormError::Malformed("part without name"))?;
+
+        match headers.file_name {
+            Some(file_name) => {
+                let (path, mut file) = create_temp_file()?;
+                // 실패하더라도 임시 파일은 UploadedFile의 drop에서 정리됨
+                let mut uploaded = UploadedFile {
+                    field: name,
+                    file_name: Some(file_name),
+                    content_type: headers.content_type,
+                    path,
+                    size: 0,
+                };
+                uploaded.size =
+                    reader.read_until(delimiter.as_bytes(), &mut file, limits.max_file_size)?;
+                file.flush()?;
+                form.files.push(uploaded);
+            }
+            None => {
+                let mut value = Vec::new();
+                reader.read_until(
+                    delimiter.as_bytes(),
+                    &mut value,
+                    limits.max_field_size as u64,
+                )?;
+                form.push_field(name, String::from_utf8_lossy(&value).into_owned());
+            }
+        }
+    }
+
+    Ok(form)
+}
+
+/// Content-Type 헤더에서 boundary 파라미터 추출
+pub fn multipart_boundary(content_type: &str) -> Option<String> {
+    let mut params = content_type.split(';').map(|param| param.trim());
+    let mime = params.next()?;
+    if !mime.eq_ignore_ascii_case("multipart/form-data") {
+        return None;
+    }
+
+    params
+        .filter_map(|param| param.split_once('='))
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case("boundary"))
+        .map(|(_, value)| value.trim().trim_matches('"').to_string())
+        .filter(|boundary| !boundary.is_empty())
+}
+
+#[derive(Default)]
+struct PartHeaders {
+    name: Option<String>,
+    file_name: Option<String>,
+    content_type: Option<String>,
+}
+
+/// 경계 탐색을 위해 읽은 데이터를 직접 버퍼링하는 리더
+/// BufRead는 읽은 데이터를 되돌릴 수 없으므로, 경계 뒤에 남은 데이터를 buf에 보관한다.
+struct MultipartReader<R> {
+    reader: R,
+    buf: Vec<u8>,
+    eof: bool,
+}
+
+impl<R: BufRead> MultipartReader<R> {
+    fn new(reader: R) -> Self {
+        MultipartReader {
+            reader,
+            buf: Vec::new(),
+            eof: false,
+        }
+    }
+
+    /// 내부 리더에서 한 덩어리를 더 읽어 buf에 붙임. EOF면 false
+    fn fill(&mut self) -> io::Result<bool> {
+        if self.eof {
+            return Ok(false);
+        }
+        let chunk = self.reader.fill_buf()?;
+        if chunk.is_empty() {
+            self.eof = true;
+            return Ok(false);
+        }
+        let len = chunk.len();
+        self.buf.extend_from_slice(chunk);
+        self.reader.consume(len);
+        Ok(true)
+    }
+
+    /// CRLF로 끝나는 한 줄을 읽음(CRLF 제외)
+    fn read_line(&mut self, limit: usize) -> Result<Vec<u8>, FormError> {
+        loop {
+            if let Some(pos) = find(&self.buf, b"\r\n") {
+                let line = self.buf[..pos].to_vec();
+                self.buf.drain(..pos + 2);
+                return Ok(line);
+            }
+            if self.buf.len() > limit {
+                return Err(FormError::TooLarge("header line"));
+            }
+            if !self.fill()? {
+                // 마지막 경계 뒤에는 CRLF가 없을 수 있음
+                return Ok(std::mem::take(&mut self.buf));
+            }
+        }
+    }
+
+    fn read_part_headers(&mut self, limit: usize) -> Result<PartHeaders, FormError> {
+        let mut headers = PartHeaders::default();
+        loop {
+            let line = self.read_line(limit)?;
+            if line.is_empty() {
+                return Ok(headers);
+            }
+            if self.eof && self.buf.is_empty() {
+                return Err(FormError::Malformed("unexpected end of part headers"));
+            }
+
+            let line = String::from_utf8_lossy(&line);
+            let Some((key, value)) = line.split_once(':') else {
+                return Err(FormError::Malformed("part header"));
+            };
+            let key = key.trim();
+            let value = value.trim();
+
+            if key.eq_ignore_ascii_case("content-disposition") {
+                for param in value.split(';').skip(1) {
+                    let Some((param_key, param_value)) = param.trim().split_once('=') else {
+                        continue;
+                    };
+                    let param_value = param_value.trim().trim_matches('"').to_string();
+                    match param_key.trim() {
+                        "name" => headers.name = Some(param_value),
+                        "filename" => headers.file_name = Some(param_value),
+                        _ => {}
+                    }
+                }
+            } else if key.eq_ignore_ascii_case("content-type") {
+                headers.content_type = Some(value.to_string());
+            }
+        }
+    }
+
+    /// delimiter 직전까지의 데이터를 out으로 흘려 보내고 delimiter는 소비
+    /// 반환값은 기록한 바이트 수
+    fn read_until<W: Write>(
+        &mut self,
+        delimiter: &[u8],
+        out: &mut W,
+        limit: u64,
+    ) -> Result<u64, FormError> {
+        let mut written = 0u64;
+        loop {
+            if let Some(pos) = find(&self.buf, delimiter) {
+                written += pos as u64;
+                if written > limit {
+                    return Err(FormError::TooLarge("form part"));
+                }
+                out.write_all(&self.buf[..pos])?;
+                self.buf.drain(..pos + delimiter.len());
+                return Ok(written);
+            }
+
+            // delimiter가 버퍼 경계에 걸쳐 있을 수 있으므로 끝 부분은 남겨 둔다.
+            let safe = self.buf.len().saturating_sub(delimiter.len() - 1);
+            if safe > 0 {
+                written += safe as u64;
+                if written > limit {
+                    return Err(FormError::TooLarge("form part"));
+                }
+                out.write_all(&self.buf[..safe])?;
+                self.buf.drain(..safe);
+            }
+
+            if !self.fill()? {
+                return Err(FormError::Malformed("missing closing boundary"));
+            }
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+fn create_temp_file() -> io::Result<(PathBuf, File)> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    loop {
+        let id = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let path =
+            std::env::temp_dir().join(format!("chapter20-upload-{}-{id}", std::process::id()));
+        // 같은 이름의 파일이 남아 있으면 다음 번호로
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_decodes_percent_encoding() {
+        assert_eq!("a b+c", percent_decode("a%20b%2Bc", false));
+        assert_eq!("a b c", percent_decode("a+b%20c", true));
+        assert_eq!("안녕", percent_decode("%EC%95%88%EB%85%95", true));
+        assert_eq!("100%", percent_decode("100%", true));
+        assert_eq!("%zz", percent_decode("%zz", true));
+    }
+
+    #[test]
+    fn it_encodes_path_segment() {
+        assert_eq!("a%20b%2F%3C%3E", percent_encode("a b/<>"));
+        assert_eq!("%EC%95%88", percent_encode("안"));
+        assert_eq!("안", percent_decode(&percent_encode("안"), false));
+    }
+
+    #[test]
+    fn it_parses_urlencoded_pairs() {
+        let form = parse_urlencoded("name=rust+lang&tag=a&tag=b&empty&=x");
+
+        assert_eq!(Some("rust lang"), form.get("name"));
+        assert_eq!(&["a", "b"], form.get_all("tag"));
+        assert_eq!(Some(""), form.get("empty"));
+        assert_eq!(None, form.get("missing"));
+    }
+
+    #[test]
+    fn it_parses_multipart_fields_and_files() {
+        let body = "preamble\r\n\
+--XYZ\r\n\
+Content-Disposition: form-data; name=\"title\"\r\n\
+\r\n\
+hello\r\n\
+--XYZ\r\n\
+Content-Disposition: form-data; name=\"upload\"; filename=\"a.txt\"\r\n\
+Content-Type: text/plain\r\n\
+\r\n\
+line1\r\nline2\r\n\
+--XYZ--\r\n";
+
+        let form = parse_multipart(body.as_bytes(), "XYZ", &FormLimits::default()).unwrap();
+
+        assert_eq!(Some("hello"), form.get("title"));
+        let file = form.file("upload").unwrap();
+        assert_eq!(Some("a.txt"), file.file_name.as_deref());
+        assert_eq!(Some("text/plain"), file.content_type.as_deref());
+        assert_eq!(b"line1\r\nline2".to_vec(), file.read_to_vec().unwrap());
+        assert_eq!(12, file.size);
+
+        let path = file.path.clone();
+        drop(form);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn it_rejects_too_large_file() {
+        let body = "--XYZ\r\n\
+Content-Disposition: form-data; name=\"upload\"; filename=\"a.txt\"\r\n\
+\r\n\
+0123456789\r\n\
+--XYZ--\r\n";
+        let limits = FormLimits {
+            max_file_size: 4,
+            ..FormLimits::default()
+        };
+
+        let result = parse_multipart(body.as_bytes(), "XYZ", &limits);
+        assert!(matches!(result, Err(FormError::TooLarge(_))));
+    }
+
+    #[test]
+    fn it_reads_boundary_from_content_type() {
+        assert_eq!(
+            Some(String::from("abc")),
+            multipart_boundary("multipart/form-data; boundary=\"abc\"")
+        );
+        assert_eq!(None, multipart_boundary("text/plain; boundary=abc"));
+    }
+}
@@ -0,0 +1,9 @@
+pub mod cgi;
+pub mod form;
+pub mod listing;
+pub mod request;
+pub mod response;
+pub mod server;
+pub mod static_files;
+pub mod template;
+pub mod threadpool;
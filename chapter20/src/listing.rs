@@ -0,0 +1,302 @@
+use std::{cmp::Ordering, fs, io, path::Path, time::UNIX_EPOCH};
+
+use crate::{
+    form::{percent_encode, FormData},
+    template::html_escape,
+};
+
+/// 디렉토리 목록의 항목 하나
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub name: String,
+    pub is_dir: bool,
+    pub size: u64,
+    /// UNIX 시간(초). 알 수 없으면 None
+    pub modified: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortKey {
+    Name,
+    Size,
+    Modified,
+}
+
+/// `?sort=name|size|modified&order=asc|desc` 쿼리로 정렬 기준 지정
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sort {
+    pub key: SortKey,
+    pub descending: bool,
+}
+
+impl Sort {
+    pub fn from_query(query: &FormData) -> Self {
+        let key = match query.get("sort") {
+            Some("size") => SortKey::Size,
+            Some("modified") => SortKey::Modified,
+            _ => SortKey::Name,
+        };
+        let descending = query.get("order") == Some("desc");
+
+        Sort { key, descending }
+    }
+
+    fn query(&self, key: SortKey) -> String {
+        let name = match key {
+            SortKey::Name => "name",
+            SortKey::Size => "size",
+            SortKey::Modified => "modified",
+        };
+        // 현재 기준을 다시 누르면 정렬 방향을 뒤집음
+        let order = if self.key == key && !self.descending {
+            "desc"
+        } else {
+            "asc"
+        };
+
+        format!("?sort={name}&order={order}")
+    }
+}
+
+/// 디렉토리 내용을 읽어 정렬된 항목 목록 반환
+/// 디렉토리는 항상 파일보다 앞에 온다.
+pub fn read_entries(dir: &Path, sort: Sort) -> io::Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_secs());
+
+        entries.push(Entry {
+            name: entry.file_name().to_string_lossy().into_owned(),
+            is_dir: metadata.is_dir(),
+            size: if metadata.is_dir() { 0 } else { metadata.len() },
+            modified,
+        });
+    }
+
+    entries.sort_by(|a, b| {
+        let ordering = match sort.key {
+            SortKey::Name => a.name.cmp(&b.name),
+            SortKey::Size => a.size.cmp(&b.size).then_with(|| a.name.cmp(&b.name)),
+            SortKey::Modified => a
+                .modified
+                .cmp(&b.modified)
+                .then_with(|| a.name.cmp(&b.name)),
+        };
+        let ordering = if sort.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        };
+
+        match (a.is_dir, b.is_dir) {
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            _ => ordering,
+        }
+    });
+
+    Ok(entries)
+}
+
+/// HTML 목록 페이지
+/// 파일 이름은 사용자가 만들 수 있는 값이므로 반드시 이스케이프한다.
+pub fn render_html(url_path: &str, entries: &[Entry], sort: Sort) -> String {
+    let title = html_escape(url_path);
+    let mut rows = String::new();
+
+    if url_path != "/" {
+        rows.push_str("      <tr><td><a href=\"../\">../</a></td><td></td><td></td></tr>\n");
+    }
+    for entry in entries {
+        let suffix = if entry.is_dir { "/" } else { "" };
+        let size = if entry.is_dir {
+            String::from("-")
+        } else {
+            entry.size.to_string()
+        };
+        let modified = entry.modified.map(format_timestamp).unwrap_or_default();
+
+        rows.push_str(&format!(
+            "      <tr><td><a href=\"{href}{suffix}\">{name}{suffix}</a></td><td>{size}</td><td>{modified}</td></tr>\n",
+            href = percent_encode(&entry.name),
+            name = html_escape(&entry.name),
+        ));
+    }
+
+    format!(
+        "<!DOCTYPE html>
+<html lang=\"en\">
+  <head>
+    <meta charset=\"utf-8\">
+    <title>Index of {title}</title>
+  </head>
+  <body>
+    <h1>Index of {title}</h1>
+    <table>
+      <tr><th><a href=\"{by_name}\">Name</a></th><th><a href=\"{by_size}\">Size</a></th><th><a href=\"{by_modified}\">Modified</a></th></tr>
+{rows}    </table>
+  </body>
+</html>",
+        by_name = html_escape(&sort.query(SortKey::Name)),
+        by_size = html_escape(&sort.query(SortKey::Size)),
+        by_modified = html_escape(&sort.query(SortKey::Modified)),
+    )
+}
+
+/// JSON 목록
+pub fn render_json(url_path: &str, entries: &[Entry]) -> String {
+    let items: Vec<String> = entries
+        .iter()
+        .map(|entry| {
+            let modified = entry
+                .modified
+                .map(|secs| format!("\"{}\"", format_timestamp(secs)))
+                .unwrap_or_else(|| String::from("null"));
+            format!(
+                "{{\"name\":{},\"type\":\"{}\",\"size\":{},\"modified\":{}}}",
+                json_string(&entry.name),
+                if entry.is_dir { "directory" } else { "file" },
+                entry.size,
+                modified,
+            )
+        })
+        .collect();
+
+    format!(
+        "{{\"path\":{},\"entries\":[{}]}}",
+        json_string(url_path),
+        items.join(",")
+    )
+}
+
+fn json_string(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len() + 2);
+    escaped.push('"');
+    for ch in input.chars() {
+        match ch {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            // `<`도 이스케이프해 HTML 안에 넣어도 안전하게
+            ch if (ch as u32) < 0x20 || ch == '<' => {
+                escaped.push_str(&format!("\\u{:04x}", ch as u32))
+            }
+            _ => escaped.push(ch),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// UNIX 시간을 `YYYY-MM-DD HH:MM:SS` (UTC) 형태로
+fn format_timestamp(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    let (year, month, day) = civil_from_days(days);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+/// 1970-01-01로부터 지난 일 수를 그레고리력 날짜로 변환
+/// (Howard Hinnant의 civil_from_days 알고리즘)
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, is_dir: bool, size: u64, modified: u64) -> Entry {
+        Entry {
+            name: name.to_string(),
+            is_dir,
+            size,
+            modified: Some(modified),
+        }
+    }
+
+    #[test]
+    fn it_escapes_file_names() {
+        let entries = vec![entry("<script>&\".txt", false, 3, 0)];
+        let html = render_html("/files/", &entries, Sort::from_query(&FormData::default()));
+
+        assert!(html.contains("&lt;script&gt;&amp;&quot;.txt"));
+        assert!(html.contains("href=\"%3Cscript%3E%26%22.txt\""));
+        assert!(!html.contains("<script>"));
+
+        let json = render_json("/files/", &entries);
+        assert!(json.contains("\"name\":\"\\u003cscript>&\\\".txt\""));
+    }
+
+    #[test]
+    fn it_formats_timestamp() {
+        assert_eq!("1970-01-01 00:00:00", format_timestamp(0));
+        assert_eq!("2024-02-29 12:34:56", format_timestamp(1_709_210_096));
+    }
+
+    #[test]
+    fn it_sorts_directories_first() {
+        let dir = std::env::temp_dir().join(format!("chapter20-listing-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("c")).unwrap();
+        fs::create_dir_all(dir.join("z")).unwrap();
+        fs::write(dir.join("a.txt"), "0123456789").unwrap();
+        fs::write(dir.join("b.txt"), "01234").unwrap();
+
+        let names = |sort: Sort| -> Vec<String> {
+            read_entries(&dir, sort)
+                .unwrap()
+                .into_iter()
+                .map(|entry| entry.name)
+                .collect()
+        };
+        let sort = |key, descending| Sort { key, descending };
+
+        assert_eq!(
+            vec!["c", "z", "a.txt", "b.txt"],
+            names(sort(SortKey::Name, false))
+        );
+        assert_eq!(
+            vec!["c", "z", "b.txt", "a.txt"],
+            names(sort(SortKey::Size, false))
+        );
+        // 내림차순이어도 디렉토리가 먼저
+        assert_eq!(
+            vec!["z", "c", "b.txt", "a.txt"],
+            names(sort(SortKey::Name, true))
+        );
+        assert_eq!(
+            vec!["z", "c", "a.txt", "b.txt"],
+            names(sort(SortKey::Size, true))
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
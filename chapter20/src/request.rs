@@ -0,0 +1,240 @@
+use std::{
+    error::Error,
+    fmt,
+    io::{self, BufRead, Read},
+    net::SocketAddr,
+};
+
+use crate::form::{self, FormData, FormError, FormLimits};
+
+/// 요청 라인 / 헤더 한 줄의 최대 길이
+const MAX_LINE_SIZE: usize = 8 * 1024;
+const MAX_HEADERS: usize = 100;
+
+/// 파싱된 HTTP 요청
+pub struct Request {
+    pub method: String,
+    /// 쿼리 스트링을 제외하고 퍼센트 디코딩된 경로
+    pub path: String,
+    pub version: String,
+    pub headers: Vec<(String, String)>,
+    /// `?` 뒤의 쿼리 스트링(디코딩 전 원본)
+    pub query_string: String,
+    /// 파싱된 쿼리 스트링
+    pub query: FormData,
+    /// urlencoded / multipart 본문
+    pub form: FormData,
+    /// 폼이 아닌 본문은 그대로 보관
+    pub body: Vec<u8>,
+    /// TCP 연결일 때 클라이언트 주소와 요청을 받은 서버 주소 (CGI의 REMOTE_ADDR, SERVER_PORT)
+    pub remote_addr: Option<SocketAddr>,
+    pub local_addr: Option<SocketAddr>,
+}
+
+impl Request {
+    /// 요청 라인, 헤더, 본문 순서로 읽어 Request를 만든다.
+    pub fn parse<R: BufRead>(reader: &mut R, limits: &FormLimits) -> Result<Self, RequestError> {
+        let mut request = Self::parse_head(reader)?;
+        request.read_body(reader, limits)?;
+
+        Ok(request)
+    }
+
+    /// 요청 라인과 헤더까지만 읽음
+    /// 본문을 직접 다뤄야 하는 경우(CGI 등) reader에 본문이 그대로 남는다.
+    pub fn parse_head<R: BufRead>(reader: &mut R) -> Result<Self, RequestError> {
+        let request_line = read_line(reader)?.ok_or(RequestError::Empty)?;
+
+        let mut parts = request_line.split_whitespace();
+        let (Some(method), Some(target), Some(version), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(RequestError::Malformed("request line"));
+        };
+
+        let mut headers = Vec::new();
+        loop {
+            let line = read_line(reader)?.ok_or(RequestError::Malformed("headers"))?;
+            if line.is_empty() {
+                break;
+            }
+            if headers.len() >= MAX_HEADERS {
+                return Err(RequestError::TooLarge("headers"));
+            }
+            let (name, value) = line
+                .split_once(':')
+                .ok_or(RequestError::Malformed("header"))?;
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+        Ok(Request {
+            method: method.to_string(),
+            path: form::percent_decode(path, false),
+            version: version.to_string(),
+            headers,
+            query_string: query.to_string(),
+            query: form::parse_urlencoded(query),
+            form: FormData::default(),
+            body: Vec::new(),
+            remote_addr: None,
+            local_addr: None,
+        })
+    }
+
+    /// 헤더 이름은 대소문자를 구분하지 않음
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Content-Length 헤더 값. 없으면 0
+    pub fn content_length(&self) -> Result<u64, RequestError> {
+        match self.header("Content-Length") {
+            Some(length) => length
+                .parse::<u64>()
+                .map_err(|_| RequestError::Malformed("content-length")),
+            None => Ok(0),
+        }
+    }
+
+    /// 본문을 Content-Type에 맞게 form / body로 읽음
+    pub fn read_body<R: BufRead>(
+        &mut self,
+        reader: &mut R,
+        limits: &FormLimits,
+    ) -> Result<(), RequestError> {
+        let length = self.content_length()?;
+        if length == 0 {
+            return Ok(());
+        }
+        if length > limits.max_body_size {
+            return Err(RequestError::TooLarge("body"));
+        }
+
+        // Content-Length 만큼만 읽도록 제한
+        let mut body = reader.by_ref().take(length);
+        let content_type = self.header("Content-Type").unwrap_or("").to_string();
+        let mime = content_type.split(';').next().unwrap_or("").trim();
+
+        if mime.eq_ignore_ascii_case("application/x-www-form-urlencoded") {
+            let mut buf = String::new();
+            body.read_to_string(&mut buf)
+                .map_err(|_| RequestError::Malformed("urlencoded body"))?;
+            self.form = form::parse_urlencoded(&buf);
+        } else if mime.eq_ignore_ascii_case("multipart/form-data") {
+            let boundary = form::multipart_boundary(&content_type)
+                .ok_or(RequestError::Malformed("multipart boundary"))?;
+            self.form = form::parse_multipart(&mut body, &boundary, limits)?;
+        } else {
+            body.read_to_end(&mut self.body)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// CRLF(또는 LF)로 끝나는 한 줄. 스트림이 끝났다면 None
+fn read_line<R: BufRead>(reader: &mut R) -> Result<Option<String>, RequestError> {
+    let mut line = Vec::new();
+    let read = reader
+        .by_ref()
+        .take(MAX_LINE_SIZE as u64 + 1)
+        .read_until(b'\n', &mut line)?;
+    if read == 0 {
+        return Ok(None);
+    }
+    if line.len() > MAX_LINE_SIZE {
+        return Err(RequestError::TooLarge("line"));
+    }
+
+    let line = String::from_utf8(line).map_err(|_| RequestError::Malformed("encoding"))?;
+    Ok(Some(line.trim_end_matches(['\r', '\n']).to_string()))
+}
+
+#[derive(Debug)]
+pub enum RequestError {
+    /// 아무 데이터 없이 연결이 닫힘
+    Empty,
+    Io(io::Error),
+    TooLarge(&'static str),
+    Malformed(&'static str),
+}
+
+impl RequestError {
+    /// 에러에 대응하는 응답 상태 라인
+    pub fn status_line(&self) -> &'static str {
+        match self {
+            RequestError::TooLarge(_) => "HTTP/1.1 413 PAYLOAD TOO LARGE",
+            _ => "HTTP/1.1 400 BAD REQUEST",
+        }
+    }
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestError::Empty => write!(f, "empty request"),
+            RequestError::Io(e) => write!(f, "io error: {e}"),
+            RequestError::TooLarge(what) => write!(f, "{what} is too large"),
+            RequestError::Malformed(what) => write!(f, "malformed {what}"),
+        }
+    }
+}
+
+impl Error for RequestError {}
+
+impl From<io::Error> for RequestError {
+    fn from(e: io::Error) -> Self {
+        RequestError::Io(e)
+    }
+}
+
+impl From<FormError> for RequestError {
+    fn from(e: FormError) -> Self {
+        match e {
+            FormError::Io(e) => RequestError::Io(e),
+            FormError::TooLarge(what) => RequestError::TooLarge(what),
+            FormError::Malformed(what) => RequestError::Malformed(what),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_query_and_urlencoded_body() {
+        let raw = "POST /submit%20form?page=2&q=a+b HTTP/1.1\r\n\
+Host: localhost\r\n\
+Content-Type: application/x-www-form-urlencoded\r\n\
+Content-Length: 20\r\n\
+\r\n\
+name=%ED%95%9C&age=3";
+        let req = Request::parse(&mut raw.as_bytes(), &FormLimits::default()).unwrap();
+
+        assert_eq!("POST", req.method);
+        assert_eq!("/submit form", req.path);
+        assert_eq!(Some("2"), req.query.get("page"));
+        assert_eq!(Some("a b"), req.query.get("q"));
+        assert_eq!(Some("한"), req.form.get("name"));
+        assert_eq!(Some("3"), req.form.get("age"));
+        assert_eq!(Some("localhost"), req.header("host"));
+    }
+
+    #[test]
+    fn it_rejects_body_over_limit() {
+        let raw = "POST / HTTP/1.1\r\nContent-Length: 100\r\n\r\n";
+        let limits = FormLimits {
+            max_body_size: 10,
+            ..FormLimits::default()
+        };
+
+        let result = Request::parse(&mut raw.as_bytes(), &limits);
+        assert!(matches!(result, Err(RequestError::TooLarge("body"))));
+    }
+}
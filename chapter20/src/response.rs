@@ -0,0 +1,48 @@
+use std::io::{self, Write};
+
+/// 응답 상태 라인, 헤더, 본문
+/// 파일 내용을 그대로 보낼 수 있도록 본문은 바이트로 관리
+pub struct Response {
+    pub status_line: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    /// HEAD 요청에 대한 응답이면 헤더만 보냄. Content-Length는 본문 길이 그대로
+    pub head_only: bool,
+}
+
+impl Response {
+    pub fn new(status_line: &str, content_type: &str, body: impl Into<Vec<u8>>) -> Self {
+        Response {
+            status_line: status_line.to_string(),
+            headers: vec![(String::from("Content-Type"), content_type.to_string())],
+            body: body.into(),
+            head_only: false,
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// GET과 같은 헤더로 답하되 본문은 보내지 않음 (HEAD)
+    pub fn without_body(mut self) -> Self {
+        self.head_only = true;
+        self
+    }
+
+    /// Content-Length는 본문 길이로 자동 계산
+    pub fn write_to<W: Write>(&self, stream: &mut W) -> io::Result<()> {
+        let mut head = format!("{}\r\n", self.status_line);
+        for (name, value) in &self.headers {
+            head.push_str(&format!("{name}: {value}\r\n"));
+        }
+        head.push_str(&format!("Content-Length: {}\r\n\r\n", self.body.len()));
+
+        stream.write_all(head.as_bytes())?;
+        if self.head_only {
+            return Ok(());
+        }
+        stream.write_all(&self.body)
+    }
+}
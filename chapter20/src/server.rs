@@ -0,0 +1,151 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpStream},
+    path::PathBuf,
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    cgi::Cgi,
+    form::FormLimits,
+    request::{Request, RequestError},
+    response::Response,
+    static_files::StaticFiles,
+    template::{Templates, Value},
+};
+
+/// 연결 하나를 처리하는 데 필요한 설정 묶음
+/// 스레드 풀의 각 작업에서 공유할 수 있도록 Arc로 감싸서 사용
+pub struct Server {
+    static_files: StaticFiles,
+    templates: Templates,
+    cgi: Option<Cgi>,
+    limits: FormLimits,
+    hello_delay: Duration,
+    read_timeout: Duration,
+}
+
+impl Server {
+    pub fn new(static_files: StaticFiles) -> Self {
+        Server {
+            static_files,
+            templates: Templates::new("templates"),
+            cgi: None,
+            limits: FormLimits::default(),
+            hello_delay: Duration::ZERO,
+            read_timeout: Duration::from_secs(30),
+        }
+    }
+
+    /// `GET /` 응답 전에 기다리는 시간(느린 요청 흉내)
+    pub fn hello_delay(mut self, delay: Duration) -> Self {
+        self.hello_delay = delay;
+        self
+    }
+
+    /// 페이지 템플릿을 읽어 올 디렉토리
+    pub fn templates(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.templates = Templates::new(dir);
+        self
+    }
+
+    /// 외부 프로그램으로 처리할 CGI 라우트
+    pub fn cgi(mut self, cgi: Cgi) -> Self {
+        self.cgi = Some(cgi);
+        self
+    }
+
+    pub fn limits(mut self, limits: FormLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// TCP 연결에서 요청을 읽을 때 이 시간 동안 아무것도 오지 않으면 포기
+    /// 요청을 덜 보내고 연결을 열어 둔 클라이언트가 작업 스레드를 붙잡지 않도록
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = timeout;
+        self
+    }
+
+    /// TcpStream 뿐만 아니라 Read + Write를 구현한 모든 스트림 처리 가능
+    /// 테스트에서는 메모리 버퍼를 스트림으로 넘긴다.
+    pub fn handle_connection<S: Read + Write>(&self, stream: S) {
+        self.serve(stream, None);
+    }
+
+    /// TCP 연결은 양쪽 주소를 요청에 기록한 뒤 처리
+    pub fn handle_tcp(&self, stream: TcpStream) {
+        let addrs = stream.peer_addr().ok().zip(stream.local_addr().ok());
+        // 0이 아닌 시간이면 실패하지 않음
+        let _ =
+            stream.set_read_timeout(Some(self.read_timeout).filter(|timeout| !timeout.is_zero()));
+        self.serve(stream, addrs);
+    }
+
+    /// addrs는 (클라이언트 주소, 서버 주소)
+    fn serve<S: Read + Write>(&self, mut stream: S, addrs: Option<(SocketAddr, SocketAddr)>) {
+        // 헤더 뿐만 아니라 쿼리 스트링 / 폼 본문까지 파싱
+        let mut buf_reader = BufReader::new(&mut stream);
+        let response = match self.respond(&mut buf_reader, addrs) {
+            Ok(response) => response,
+            Err(err) => Response::new(err.status_line(), "text/plain", err.to_string()),
+        };
+
+        // 클라이언트가 먼저 연결을 끊은 경우는 무시
+        let _ = response.write_to(&mut stream);
+    }
+
+    fn respond<R: BufRead>(
+        &self,
+        reader: &mut R,
+        addrs: Option<(SocketAddr, SocketAddr)>,
+    ) -> Result<Response, RequestError> {
+        let mut req = Request::parse_head(reader)?;
+        if let Some((remote, local)) = addrs {
+            req.remote_addr = Some(remote);
+            req.local_addr = Some(local);
+        }
+
+        // CGI 라우트는 본문을 파싱하지 않고 그대로 스크립트에 넘김
+        if let Some(cgi) = &self.cgi {
+            if cgi.route(&req.path).is_some() {
+                if req.content_length()? > self.limits.max_body_size {
+                    return Err(RequestError::TooLarge("body"));
+                }
+                return Ok(cgi.run(&req, reader));
+            }
+        }
+
+        req.read_body(reader, &self.limits)?;
+        Ok(self.handle(&req))
+    }
+
+    /// 요청에 대한 응답 생성
+    pub fn handle(&self, req: &Request) -> Response {
+        let (status_line, filename, context) = if req.method == "GET" && req.path == "/" {
+            thread::sleep(self.hello_delay);
+            let context = Value::map()
+                .insert("title", "Hello!")
+                .insert("name", req.query.get("name").unwrap_or(""));
+            ("HTTP/1.1 200 OK", "hello.html", context)
+        } else if let Some(response) = self.static_files.serve(req) {
+            return response;
+        } else {
+            let context = Value::map()
+                .insert("title", "Hello!")
+                .insert("path", req.path.as_str())
+                .insert("links", vec!["/"]);
+            ("HTTP/1.1 404 NOT FOUND", "404.html", context)
+        };
+
+        match self.templates.render(filename, &context) {
+            Ok(content) => Response::new(status_line, "text/html; charset=utf-8", content),
+            Err(err) => Response::new(
+                "HTTP/1.1 500 INTERNAL SERVER ERROR",
+                "text/plain",
+                err.to_string(),
+            ),
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{
+    form::percent_encode,
+    listing::{self, Sort},
+    request::Request,
+    response::Response,
+};
+
+/// 루트 디렉토리 아래의 정적 파일 제공
+pub struct StaticFiles {
+    root: PathBuf,
+    directory_listing: bool,
+}
+
+impl StaticFiles {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        StaticFiles {
+            root: root.into(),
+            directory_listing: false,
+        }
+    }
+
+    /// index.html이 없는 디렉토리에 대해 목록 페이지를 만들지 여부
+    pub fn directory_listing(mut self, enabled: bool) -> Self {
+        self.directory_listing = enabled;
+        self
+    }
+
+    /// 요청 경로에 해당하는 파일이 없으면 None
+    /// HEAD 요청은 GET과 같은 헤더(Content-Length 포함)로 답하고 본문은 보내지 않음
+    pub fn serve(&self, req: &Request) -> Option<Response> {
+        match req.method.as_str() {
+            "GET" => self.serve_get(req),
+            "HEAD" => self.serve_get(req).map(Response::without_body),
+            _ => None,
+        }
+    }
+
+    fn serve_get(&self, req: &Request) -> Option<Response> {
+        let path = self.resolve(&req.path)?;
+
+        if path.is_file() {
+            let content = fs::read(&path).ok()?;
+            return Some(Response::new(
+                "HTTP/1.1 200 OK",
+                content_type(&path),
+                content,
+            ));
+        }
+        if !path.is_dir() {
+            return None;
+        }
+
+        // 디렉토리는 `/`로 끝나는 주소로 맞춰야 상대 링크가 올바르게 동작
+        if !req.path.ends_with('/') {
+            let encoded: Vec<String> = req.path.split('/').map(percent_encode).collect();
+            let location = format!("{}/", encoded.join("/"));
+            return Some(
+                Response::new("HTTP/1.1 301 MOVED PERMANENTLY", "text/plain", "")
+                    .with_header("Location", &location),
+            );
+        }
+
+        let index = path.join("index.html");
+        if index.is_file() {
+            let content = fs::read(&index).ok()?;
+            return Some(Response::new(
+                "HTTP/1.1 200 OK",
+                "text/html; charset=utf-8",
+                content,
+            ));
+        }
+        if !self.directory_listing {
+            return None;
+        }
+
+        let sort = Sort::from_query(&req.query);
+        let entries = listing::read_entries(&path, sort).ok()?;
+
+        if prefers_json(req.header("Accept")) {
+            let body = listing::render_json(&req.path, &entries);
+            Some(Response::new("HTTP/1.1 200 OK", "application/json", body))
+        } else {
+            let body = listing::render_html(&req.path, &entries, sort);
+            Some(Response::new(
+                "HTTP/1.1 200 OK",
+                "text/html; charset=utf-8",
+                body,
+            ))
+        }
+    }
+
+    /// 요청 경로를 루트 아래의 실제 경로로 변환
+    /// `..` 등으로 루트 밖을 가리키는 경로는 거부
+    fn resolve(&self, url_path: &str) -> Option<PathBuf> {
+        let mut path = self.root.clone();
+
+        for component in Path::new(url_path.trim_start_matches('/')).components() {
+            match component {
+                Component::Normal(part) => path.push(part),
+                Component::CurDir => {}
+                _ => return None,
+            }
+        }
+
+        Some(path)
+    }
+}
+
+/// Accept 헤더에서 JSON이 HTML보다 먼저 나오면 JSON 목록을 돌려준다.
+fn prefers_json(accept: Option<&str>) -> bool {
+    let Some(accept) = accept else {
+        return false;
+    };
+    let position = |mime: &str| accept.find(mime);
+
+    match (position("application/json"), position("text/html")) {
+        (Some(json), Some(html)) => json < html,
+        (Some(_), None) => true,
+        _ => false,
+    }
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("html") | Some("htm") => "text/html; charset=utf-8",
+        Some("css") => "text/css",
+        Some("js") => "text/javascript",
+        Some("json") => "application/json",
+        Some("txt") | Some("md") => "text/plain; charset=utf-8",
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("svg") => "image/svg+xml",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_rejects_paths_outside_root() {
+        let files = StaticFiles::new("public");
+
+        assert_eq!(
+            Some(PathBuf::from("public/a/b.txt")),
+            files.resolve("/a/./b.txt")
+        );
+        assert_eq!(None, files.resolve("/../Cargo.toml"));
+        assert_eq!(None, files.resolve("/a/../../secret"));
+    }
+
+    #[test]
+    fn it_negotiates_json_listing() {
+        assert!(prefers_json(Some("application/json")));
+        assert!(!prefers_json(Some("text/html,application/json;q=0.9")));
+        assert!(!prefers_json(None));
+    }
+}
@@ -0,0 +1,526 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+/// 템플릿에 넘기는 데이터
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Str(String),
+    Bool(bool),
+    List(Vec<Value>),
+    Map(BTreeMap<String, Value>),
+}
+
+impl Value {
+    /// 빈 맵. `insert`로 값을 채워 컨텍스트로 사용
+    pub fn map() -> Self {
+        Value::Map(BTreeMap::new())
+    }
+
+    pub fn insert(mut self, key: &str, value: impl Into<Value>) -> Self {
+        if let Value::Map(map) = &mut self {
+            map.insert(key.to_string(), value.into());
+        }
+        self
+    }
+
+    /// `user.name` 처럼 점으로 구분된 경로 탐색
+    fn lookup(&self, path: &[String]) -> Option<&Value> {
+        path.iter().try_fold(self, |value, key| match value {
+            Value::Map(map) => map.get(key),
+            _ => None,
+        })
+    }
+
+    /// 빈 문자열, false, 빈 목록/맵은 거짓으로 취급
+    fn is_truthy(&self) -> bool {
+        match self {
+            Value::Str(s) => !s.is_empty(),
+            Value::Bool(b) => *b,
+            Value::List(list) => !list.is_empty(),
+            Value::Map(map) => !map.is_empty(),
+        }
+    }
+
+    fn to_text(&self) -> String {
+        match self {
+            Value::Str(s) => s.clone(),
+            Value::Bool(b) => b.to_string(),
+            Value::List(list) => list
+                .iter()
+                .map(|value| value.to_text())
+                .collect::<Vec<_>>()
+                .join(", "),
+            Value::Map(_) => String::new(),
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::Str(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::Str(s)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl From<usize> for Value {
+    fn from(n: usize) -> Self {
+        Value::Str(n.to_string())
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(list: Vec<T>) -> Self {
+        Value::List(list.into_iter().map(Into::into).collect())
+    }
+}
+
+#[derive(Debug)]
+pub enum TemplateError {
+    Io(PathBuf, io::Error),
+    Syntax(String),
+    /// include가 자기 자신을 다시 include하는 경우 등
+    TooDeep,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::Io(path, e) => write!(f, "cannot read template {}: {e}", path.display()),
+            TemplateError::Syntax(msg) => write!(f, "template syntax error: {msg}"),
+            TemplateError::TooDeep => write!(f, "template includes are nested too deeply"),
+        }
+    }
+}
+
+impl Error for TemplateError {}
+
+/// 파싱된 템플릿 구성 요소
+#[derive(Debug, PartialEq)]
+enum Node {
+    Text(String),
+    /// `{{ path }}` (HTML 이스케이프) / `{{ path | raw }}`
+    Var {
+        path: Vec<String>,
+        raw: bool,
+    },
+    /// `{% if [not] path %} ... {% else %} ... {% endif %}`
+    If {
+        path: Vec<String>,
+        negate: bool,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    /// `{% for name in path %} ... {% endfor %}`
+    For {
+        name: String,
+        path: Vec<String>,
+        body: Vec<Node>,
+    },
+    /// `{% include "file.html" %}`
+    Include(String),
+}
+
+#[derive(Debug)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self, TemplateError> {
+        let tokens = tokenize(source)?;
+        let mut tokens = tokens.into_iter();
+        let (nodes, end) = parse_nodes(&mut tokens)?;
+        match end {
+            None => Ok(Template { nodes }),
+            Some(tag) => Err(TemplateError::Syntax(format!("unexpected {{% {tag} %}}"))),
+        }
+    }
+}
+
+enum Token {
+    Text(String),
+    Var(String),
+    Tag(String),
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, TemplateError> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+
+    while !rest.is_empty() {
+        let next = [rest.find("{{"), rest.find("{%")]
+            .into_iter()
+            .flatten()
+            .min();
+        let Some(start) = next else {
+            tokens.push(Token::Text(rest.to_string()));
+            break;
+        };
+        if start > 0 {
+            tokens.push(Token::Text(rest[..start].to_string()));
+        }
+
+        let is_var = rest[start..].starts_with("{{");
+        let close = if is_var { "}}" } else { "%}" };
+        let inner_start = start + 2;
+        let end = rest[inner_start..].find(close).ok_or_else(|| {
+            TemplateError::Syntax(format!("unclosed {}", &rest[start..inner_start]))
+        })?;
+        let inner = rest[inner_start..inner_start + end].trim().to_string();
+
+        tokens.push(if is_var {
+            Token::Var(inner)
+        } else {
+            Token::Tag(inner)
+        });
+        rest = &rest[inner_start + end + 2..];
+    }
+
+    Ok(tokens)
+}
+
+/// 블록 끝(`else`, `endif`, `endfor`)을 만나면 그 태그와 함께 반환
+fn parse_nodes(
+    tokens: &mut impl Iterator<Item = Token>,
+) -> Result<(Vec<Node>, Option<String>), TemplateError> {
+    let mut nodes = Vec::new();
+
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::Text(text) => nodes.push(Node::Text(text)),
+            Token::Var(expr) => {
+                let (path, filter) = match expr.split_once('|') {
+                    Some((path, filter)) => (path.trim(), Some(filter.trim())),
+                    None => (expr.as_str(), None),
+                };
+                let raw = match filter {
+                    None => false,
+                    Some("raw") => true,
+                    Some(other) => {
+                        return Err(TemplateError::Syntax(format!("unknown filter {other}")))
+                    }
+                };
+                nodes.push(Node::Var {
+                    path: parse_path(path)?,
+                    raw,
+                });
+            }
+            Token::Tag(tag) => {
+                let words: Vec<&str> = tag.split_whitespace().collect();
+                match words.as_slice() {
+                    ["else"] | ["endif"] | ["endfor"] => return Ok((nodes, Some(tag))),
+                    ["if", "not", path] | ["if", path] => {
+                        let negate = words.len() == 3;
+                        let path = parse_path(path)?;
+                        let (then, end) = parse_nodes(tokens)?;
+                        let otherwise = match end.as_deref() {
+                            Some("endif") => Vec::new(),
+                            Some("else") => match parse_nodes(tokens)? {
+                                (otherwise, Some(end)) if end == "endif" => otherwise,
+                                _ => {
+                                    return Err(TemplateError::Syntax(String::from(
+                                        "missing endif",
+                                    )))
+                                }
+                            },
+                            _ => return Err(TemplateError::Syntax(String::from("missing endif"))),
+                        };
+                        nodes.push(Node::If {
+                            path,
+                            negate,
+                            then,
+                            otherwise,
+                        });
+                    }
+                    ["for", name, "in", path] => {
+                        let name = name.to_string();
+                        let path = parse_path(path)?;
+                        let body = match parse_nodes(tokens)? {
+                            (body, Some(end)) if end == "endfor" => body,
+                            _ => return Err(TemplateError::Syntax(String::from("missing endfor"))),
+                        };
+                        nodes.push(Node::For { name, path, body });
+                    }
+                    ["include", file] => {
+                        let file = file.trim_matches('"');
+                        nodes.push(Node::Include(file.to_string()));
+                    }
+                    _ => return Err(TemplateError::Syntax(format!("unknown tag {{% {tag} %}}"))),
+                }
+            }
+        }
+    }
+
+    Ok((nodes, None))
+}
+
+fn parse_path(path: &str) -> Result<Vec<String>, TemplateError> {
+    let parts: Vec<String> = path
+        .split('.')
+        .map(|part| part.trim().to_string())
+        .collect();
+    if parts.iter().any(|part| part.is_empty()) {
+        return Err(TemplateError::Syntax(format!("invalid variable {path:?}")));
+    }
+    Ok(parts)
+}
+
+pub fn html_escape(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for ch in input.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// include 중첩 한도. 순환 include로 인한 무한 재귀 방지
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+struct Cached {
+    modified: Option<SystemTime>,
+    template: Arc<Template>,
+}
+
+/// 디렉토리 단위 템플릿 저장소
+/// 한 번 파싱한 템플릿은 캐시하고, 파일 수정 시간이 바뀌면 다시 읽는다.
+pub struct Templates {
+    dir: PathBuf,
+    cache: Mutex<HashMap<String, Cached>>,
+}
+
+impl Templates {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Templates {
+            dir: dir.into(),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn render(&self, name: &str, context: &Value) -> Result<String, TemplateError> {
+        let mut out = String::new();
+        self.render_into(name, context, &mut out, 0)?;
+        Ok(out)
+    }
+
+    fn render_into(
+        &self,
+        name: &str,
+        context: &Value,
+        out: &mut String,
+        depth: usize,
+    ) -> Result<(), TemplateError> {
+        if depth > MAX_INCLUDE_DEPTH {
+            return Err(TemplateError::TooDeep);
+        }
+        let template = self.get(name)?;
+        let mut scopes = vec![context.clone()];
+        self.render_nodes(&template.nodes, &mut scopes, out, depth)
+    }
+
+    /// 캐시된 템플릿을 가져옴. 파일이 바뀌었으면 다시 파싱
+    fn get(&self, name: &str) -> Result<Arc<Template>, TemplateError> {
+        let path = self.dir.join(name);
+        let modified = modified_time(&path);
+
+        if let Some(cached) = self.cache.lock().unwrap().get(name) {
+            if cached.modified == modified && modified.is_some() {
+                return Ok(Arc::clone(&cached.template));
+            }
+        }
+
+        // 파싱하는 동안에는 lock을 잡지 않는다.
+        let source = fs::read_to_string(&path).map_err(|e| TemplateError::Io(path.clone(), e))?;
+        let template = Arc::new(Template::parse(&source)?);
+        self.cache.lock().unwrap().insert(
+            name.to_string(),
+            Cached {
+                modified,
+                template: Arc::clone(&template),
+            },
+        );
+
+        Ok(template)
+    }
+
+    /// scopes의 마지막 요소가 가장 안쪽 for 루프 변수
+    fn render_nodes(
+        &self,
+        nodes: &[Node],
+        scopes: &mut Vec<Value>,
+        out: &mut String,
+        depth: usize,
+    ) -> Result<(), TemplateError> {
+        for node in nodes {
+            match node {
+                Node::Text(text) => out.push_str(text),
+                Node::Var { path, raw } => {
+                    let text = resolve(scopes, path)
+                        .map(Value::to_text)
+                        .unwrap_or_default();
+                    if *raw {
+                        out.push_str(&text);
+                    } else {
+                        out.push_str(&html_escape(&text));
+                    }
+                }
+                Node::If {
+                    path,
+                    negate,
+                    then,
+                    otherwise,
+                } => {
+                    let truthy = resolve(scopes, path).is_some_and(Value::is_truthy);
+                    let branch = if truthy != *negate { then } else { otherwise };
+                    self.render_nodes(branch, scopes, out, depth)?;
+                }
+                Node::For { name, path, body } => {
+                    let items = match resolve(scopes, path) {
+                        Some(Value::List(items)) => items.clone(),
+                        _ => Vec::new(),
+                    };
+                    let len = items.len();
+                    for (idx, item) in items.into_iter().enumerate() {
+                        let scope = Value::map().insert(name, item).insert(
+                            "loop",
+                            Value::map()
+                                .insert("index", idx + 1)
+                                .insert("first", idx == 0)
+                                .insert("last", idx + 1 == len),
+                        );
+                        scopes.push(scope);
+                        let result = self.render_nodes(body, scopes, out, depth);
+                        scopes.pop();
+                        result?;
+                    }
+                }
+                Node::Include(file) => {
+                    // include된 템플릿도 현재 루프 변수를 볼 수 있도록 scope를 합쳐서 넘김
+                    let context = merge_scopes(scopes);
+                    self.render_into(file, &context, out, depth + 1)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn resolve<'a>(scopes: &'a [Value], path: &[String]) -> Option<&'a Value> {
+    scopes.iter().rev().find_map(|scope| scope.lookup(path))
+}
+
+fn merge_scopes(scopes: &[Value]) -> Value {
+    let mut merged = BTreeMap::new();
+    for scope in scopes {
+        if let Value::Map(map) = scope {
+            merged.extend(map.iter().map(|(key, value)| (key.clone(), value.clone())));
+        }
+    }
+    Value::Map(merged)
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(source: &str, context: &Value) -> String {
+        let template = Template::parse(source).unwrap();
+        let templates = Templates::new(".");
+        let mut out = String::new();
+        templates
+            .render_nodes(&template.nodes, &mut vec![context.clone()], &mut out, 0)
+            .unwrap();
+        out
+    }
+
+    #[test]
+    fn it_substitutes_and_escapes_variables() {
+        let context = Value::map()
+            .insert("name", "<b>rust</b>")
+            .insert("user", Value::map().insert("age", "9"));
+
+        assert_eq!(
+            "hi &lt;b&gt;rust&lt;/b&gt; <b>rust</b> 9 []",
+            render(
+                "hi {{ name }} {{ name | raw }} {{ user.age }} [{{ missing }}]",
+                &context
+            )
+        );
+    }
+
+    #[test]
+    fn it_renders_conditionals_and_loops() {
+        let context = Value::map()
+            .insert("items", vec!["a", "b", "c"])
+            .insert("empty", Vec::<Value>::new());
+        let source = "{% for item in items %}{{ loop.index }}{{ item }}{% if not loop.last %},{% endif %}{% endfor %}\
+{% if empty %}yes{% else %} none{% endif %}";
+
+        assert_eq!("1a,2b,3c none", render(source, &context));
+    }
+
+    #[test]
+    fn it_reports_unbalanced_blocks() {
+        assert!(Template::parse("{% if a %}x").is_err());
+        assert!(Template::parse("{% endfor %}").is_err());
+        assert!(Template::parse("{{ a ").is_err());
+    }
+
+    #[test]
+    fn it_reloads_changed_template_and_renders_includes() {
+        let dir = std::env::temp_dir().join(format!("chapter20-template-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("part.html"), "[{{ name }}]").unwrap();
+        fs::write(dir.join("page.html"), "v1 {% include \"part.html\" %}").unwrap();
+
+        let templates = Templates::new(&dir);
+        let context = Value::map().insert("name", "x");
+        assert_eq!("v1 [x]", templates.render("page.html", &context).unwrap());
+
+        // 수정 시간이 확실히 달라지도록 직접 지정
+        fs::write(dir.join("page.html"), "v2 {% include \"part.html\" %}").unwrap();
+        let later = SystemTime::now() + std::time::Duration::from_secs(5);
+        fs::File::options()
+            .write(true)
+            .open(dir.join("page.html"))
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert_eq!("v2 [x]", templates.render("page.html", &context).unwrap());
+
+        fs::write(dir.join("loop.html"), "{% include \"loop.html\" %}").unwrap();
+        assert!(matches!(
+            templates.render("loop.html", &context),
+            Err(TemplateError::TooDeep)
+        ));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
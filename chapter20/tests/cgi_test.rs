@@ -0,0 +1,168 @@
+// 셸 스크립트를 실행하므로 유닉스 계열에서만 테스트
+#![cfg(unix)]
+
+use std::{
+    fs,
+    io::{Read, Write},
+    net::TcpStream,
+    os::unix::fs::PermissionsExt,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use chapter20::cgi::Cgi;
+
+mod common;
+
+use common::{test_server, TestClient};
+
+/// 테스트마다 별도 디렉토리에 실행 가능한 스크립트 생성
+fn script_dir(name: &str, scripts: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("chapter20-cgi-{name}-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    for (file, source) in scripts {
+        let path = dir.join(file);
+        fs::write(&path, source).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    dir
+}
+
+fn cgi(dir: &PathBuf) -> Cgi {
+    Cgi::new("/cgi-bin/", dir).timeout(Duration::from_millis(500))
+}
+
+fn client(dir: &PathBuf) -> TestClient {
+    TestClient::in_memory(test_server().cgi(cgi(dir)))
+}
+
+#[test]
+fn it_passes_cgi_environment_and_body() {
+    let dir = script_dir(
+        "env",
+        &[(
+            "echo.sh",
+            "#!/bin/sh\n\
+printf 'Content-Type: text/plain\\r\\nX-Script: yes\\r\\n\\r\\n'\n\
+echo \"$GATEWAY_INTERFACE $REQUEST_METHOD $SCRIPT_NAME $PATH_INFO $QUERY_STRING $CONTENT_TYPE $HTTP_X_TOKEN\"\n\
+head -c \"$CONTENT_LENGTH\"\n",
+        )],
+    );
+
+    client(&dir)
+        .request(
+            "POST",
+            "/cgi-bin/echo.sh/extra/path?a=1&b=%20",
+            &[("Content-Type", "text/plain"), ("X-Token", "secret")],
+            b"request body",
+        )
+        .assert_status(200)
+        .assert_header("X-Script", "yes")
+        .assert_body_contains(
+            "CGI/1.1 POST /cgi-bin/echo.sh /extra/path a=1&b=%20 text/plain secret\nrequest body",
+        );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn it_uses_status_header_from_script() {
+    let dir = script_dir(
+        "status",
+        &[(
+            "missing.sh",
+            "#!/bin/sh\necho 'Status: 404 Not Found'\necho 'Content-Type: text/plain'\necho\necho gone\n",
+        )],
+    );
+
+    client(&dir)
+        .get("/cgi-bin/missing.sh")
+        .assert_status(404)
+        .assert_body_contains("gone");
+    client(&dir).get("/cgi-bin/nothing.sh").assert_status(404);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn it_kills_runaway_script() {
+    let dir = script_dir(
+        "timeout",
+        &[
+            ("slow.sh", "#!/bin/sh\nexec sleep 30\n"),
+            // exec 없이 띄운 자식 프로세스도 stdout을 쥐고 있으므로 함께 종료해야 함
+            ("child.sh", "#!/bin/sh\nsleep 30\n"),
+            // 헤더를 출력하고 끝났지만 백그라운드 자식이 stdout을 닫지 않음
+            (
+                "background.sh",
+                "#!/bin/sh\necho 'Content-Type: text/plain'\necho\nsleep 30 &\n",
+            ),
+        ],
+    );
+
+    for script in ["slow.sh", "child.sh", "background.sh"] {
+        let started = Instant::now();
+        client(&dir)
+            .get(&format!("/cgi-bin/{script}"))
+            .assert_status(504);
+        assert!(started.elapsed() < Duration::from_secs(5), "{script}");
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn it_sets_addresses_and_skips_proxy_header() {
+    let dir = script_dir(
+        "addr",
+        &[(
+            "addr.sh",
+            "#!/bin/sh\necho 'Content-Type: text/plain'\necho\n\
+echo \"remote=$REMOTE_ADDR port=$SERVER_PORT proxy=${HTTP_PROXY-unset}\"\n",
+        )],
+    );
+
+    let client = TestClient::spawn(test_server().cgi(cgi(&dir)));
+    // 기본값이 아니라 실제로 요청을 받은 임시 포트
+    let port = client.addr().unwrap().port();
+    client
+        .request(
+            "GET",
+            "/cgi-bin/addr.sh",
+            &[("Proxy", "http://evil.example:8080")],
+            b"",
+        )
+        .assert_status(200)
+        .assert_body_contains(&format!("remote=127.0.0.1 port={port} proxy=unset"));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn it_does_not_wait_for_missing_body() {
+    let dir = script_dir("body", &[("cat.sh", "#!/bin/sh\necho\ncat\n")]);
+
+    // 본문이 Content-Length보다 짧게 끝나면 스크립트를 실행하지 않음
+    client(&dir)
+        .send_raw(b"POST /cgi-bin/cat.sh HTTP/1.1\r\nContent-Length: 10\r\n\r\nabc")
+        .assert_status(400);
+
+    // 본문을 덜 보낸 채 연결을 열어 두면 읽기 제한 시간이 지난 뒤 응답
+    let client = TestClient::spawn(
+        test_server()
+            .cgi(cgi(&dir))
+            .read_timeout(Duration::from_millis(200)),
+    );
+    let mut stream = TcpStream::connect(client.addr().unwrap()).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    stream
+        .write_all(b"POST /cgi-bin/cat.sh HTTP/1.1\r\nContent-Length: 10\r\n\r\nabc")
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 408"), "{response}");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
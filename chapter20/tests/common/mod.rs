@@ -0,0 +1,213 @@
+// 테스트 파일마다 사용하는 헬퍼가 다르므로 사용하지 않는 함수 경고는 끔
+#![allow(dead_code)]
+
+use std::{
+    io::{self, Cursor, Read, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream},
+    sync::Arc,
+    thread,
+};
+
+use chapter20::{server::Server, static_files::StaticFiles, threadpool::ThreadPool};
+
+/// 테스트용 기본 서버. `GET /`의 지연 없이 바로 응답한다.
+pub fn test_server() -> Server {
+    Server::new(StaticFiles::new("public"))
+}
+
+/// 요청 바이트를 읽고, 응답 바이트를 모아두는 메모리 스트림
+pub struct MemoryStream {
+    input: Cursor<Vec<u8>>,
+    output: Vec<u8>,
+}
+
+impl MemoryStream {
+    pub fn new(input: &[u8]) -> Self {
+        MemoryStream {
+            input: Cursor::new(input.to_vec()),
+            output: Vec::new(),
+        }
+    }
+}
+
+impl Read for MemoryStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.input.read(buf)
+    }
+}
+
+impl Write for MemoryStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.output.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+enum Target {
+    /// 핸들러에 메모리 스트림을 직접 넘김
+    Memory(Box<Server>),
+    /// 임시 포트에서 실제로 실행 중인 서버
+    Tcp(SocketAddr),
+}
+
+pub struct TestClient {
+    target: Target,
+}
+
+impl TestClient {
+    pub fn in_memory(server: Server) -> Self {
+        TestClient {
+            target: Target::Memory(Box::new(server)),
+        }
+    }
+
+    /// 운영체제가 골라 준 임시 포트로 서버를 띄운다.
+    /// 서버 스레드는 테스트 프로세스가 끝날 때 함께 정리됨
+    pub fn spawn(server: Server) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = Arc::new(server);
+
+        thread::spawn(move || {
+            let pool = ThreadPool::new(2);
+            for stream in listener.incoming() {
+                let stream = stream.unwrap();
+                let server = Arc::clone(&server);
+                pool.execute(move || server.handle_tcp(stream));
+            }
+        });
+
+        TestClient {
+            target: Target::Tcp(addr),
+        }
+    }
+
+    /// spawn으로 띄운 서버의 주소
+    pub fn addr(&self) -> Option<SocketAddr> {
+        match &self.target {
+            Target::Memory(_) => None,
+            Target::Tcp(addr) => Some(*addr),
+        }
+    }
+
+    pub fn get(&self, path: &str) -> TestResponse {
+        self.request("GET", path, &[], b"")
+    }
+
+    /// 본문이 있으면 Content-Length는 자동으로 붙인다.
+    pub fn request(
+        &self,
+        method: &str,
+        path: &str,
+        headers: &[(&str, &str)],
+        body: &[u8],
+    ) -> TestResponse {
+        let mut raw = format!("{method} {path} HTTP/1.1\r\nHost: localhost\r\n");
+        for (name, value) in headers {
+            raw.push_str(&format!("{name}: {value}\r\n"));
+        }
+        if !body.is_empty() {
+            raw.push_str(&format!("Content-Length: {}\r\n", body.len()));
+        }
+        raw.push_str("\r\n");
+
+        let mut raw = raw.into_bytes();
+        raw.extend_from_slice(body);
+        self.send_raw(&raw)
+    }
+
+    /// 요청을 그대로 보내고 응답을 파싱
+    pub fn send_raw(&self, raw: &[u8]) -> TestResponse {
+        let bytes = match &self.target {
+            Target::Memory(server) => {
+                let mut stream = MemoryStream::new(raw);
+                server.handle_connection(&mut stream);
+                stream.output
+            }
+            Target::Tcp(addr) => {
+                let mut stream = TcpStream::connect(addr).unwrap();
+                stream.write_all(raw).unwrap();
+                stream.shutdown(Shutdown::Write).unwrap();
+
+                let mut bytes = Vec::new();
+                stream.read_to_end(&mut bytes).unwrap();
+                bytes
+            }
+        };
+
+        TestResponse::parse(&bytes)
+    }
+}
+
+#[derive(Debug)]
+pub struct TestResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl TestResponse {
+    fn parse(bytes: &[u8]) -> Self {
+        let head_end = bytes
+            .windows(4)
+            .position(|window| window == b"\r\n\r\n")
+            .expect("response has no header terminator");
+        let head = String::from_utf8(bytes[..head_end].to_vec()).unwrap();
+        let mut lines = head.split("\r\n");
+
+        let status_line = lines.next().unwrap();
+        let status = status_line
+            .split_whitespace()
+            .nth(1)
+            .and_then(|code| code.parse().ok())
+            .expect("invalid status line");
+        let headers = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+            .collect();
+
+        TestResponse {
+            status,
+            headers,
+            body: bytes[head_end + 4..].to_vec(),
+        }
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    pub fn assert_status(&self, status: u16) -> &Self {
+        assert_eq!(
+            status,
+            self.status,
+            "unexpected status. body: {}",
+            self.text()
+        );
+        self
+    }
+
+    pub fn assert_header(&self, name: &str, value: &str) -> &Self {
+        assert_eq!(Some(value), self.header(name), "header {name}");
+        self
+    }
+
+    pub fn assert_body_contains(&self, needle: &str) -> &Self {
+        assert!(
+            self.text().contains(needle),
+            "body does not contain {needle:?}: {}",
+            self.text()
+        );
+        self
+    }
+}
@@ -0,0 +1,108 @@
+use chapter20::server::Server;
+use chapter20::static_files::StaticFiles;
+
+mod common;
+
+use common::{test_server, TestClient};
+
+#[test]
+fn it_responds_hello_page_for_root() {
+    let client = TestClient::in_memory(test_server());
+
+    let response = client.get("/");
+    response
+        .assert_status(200)
+        .assert_header("Content-Length", &response.body.len().to_string())
+        .assert_body_contains("<title>Hello!</title>")
+        .assert_body_contains("Hi from Rust");
+    assert!(!response.text().contains("Nice to meet you"));
+}
+
+#[test]
+fn it_renders_query_into_hello_page_escaped() {
+    let client = TestClient::in_memory(test_server());
+
+    client
+        .get("/?name=%3Cb%3ERust%3C%2Fb%3E")
+        .assert_status(200)
+        .assert_body_contains("Nice to meet you, &lt;b&gt;Rust&lt;/b&gt;!");
+}
+
+#[test]
+fn it_responds_500_when_template_is_missing() {
+    let client = TestClient::in_memory(test_server().templates("no-such-dir"));
+
+    client.get("/").assert_status(500);
+}
+
+#[test]
+fn it_responds_404_for_unknown_path() {
+    let client = TestClient::in_memory(test_server());
+
+    client
+        .get("/unknown")
+        .assert_status(404)
+        .assert_body_contains("404 NOT FOUND")
+        .assert_body_contains("/unknown does not exist.");
+    // GET 이외의 메서드는 루트라도 404
+    client.request("POST", "/", &[], b"a=b").assert_status(404);
+}
+
+#[test]
+fn it_responds_400_for_malformed_request() {
+    let client = TestClient::in_memory(test_server());
+
+    client.send_raw(b"NOT-HTTP\r\n\r\n").assert_status(400);
+}
+
+#[test]
+fn it_serves_static_file_and_listing() {
+    let server = Server::new(StaticFiles::new("public").directory_listing(true));
+    let client = TestClient::in_memory(server);
+
+    client
+        .get("/files/hello.txt")
+        .assert_status(200)
+        .assert_header("Content-Type", "text/plain; charset=utf-8");
+    client
+        .get("/files")
+        .assert_status(301)
+        .assert_header("Location", "/files/");
+    client
+        .get("/files/")
+        .assert_status(200)
+        .assert_body_contains("hello.txt");
+}
+
+#[test]
+fn it_answers_head_without_body() {
+    let server = Server::new(StaticFiles::new("public").directory_listing(true));
+    let client = TestClient::in_memory(server);
+    let length = std::fs::metadata("public/files/hello.txt").unwrap().len();
+
+    let response = client.request("HEAD", "/files/hello.txt", &[], b"");
+    response
+        .assert_status(200)
+        .assert_header("Content-Type", "text/plain; charset=utf-8")
+        .assert_header("Content-Length", &length.to_string());
+    assert!(response.body.is_empty());
+
+    // 목록도 GET과 같은 길이를 알려 줌
+    let listing = client.get("/files/");
+    let response = client.request("HEAD", "/files/", &[], b"");
+    response
+        .assert_status(200)
+        .assert_header("Content-Length", &listing.body.len().to_string());
+    assert!(response.body.is_empty());
+}
+
+#[test]
+fn it_works_over_tcp_on_ephemeral_port() {
+    let client = TestClient::spawn(test_server());
+
+    client
+        .get("/")
+        .assert_status(200)
+        .assert_body_contains("Hi from Rust");
+    client.get("/unknown").assert_status(404);
+}